@@ -0,0 +1,67 @@
+// Compares the spatial indexes in geom with brute force geom::dist loops.
+// Run with `cargo bench`.
+extern crate bitlib;
+
+use std::time::{ Duration, Instant };
+use bitlib::geom::dist;
+use bitlib::geom::kdtree::KdTree;
+use bitlib::geom::point::Point;
+use bitlib::geom::spatial_hash::SpatialHash;
+use bitlib::random::Random;
+
+const COUNT: usize = 5000;
+const RADIUS: f64 = 10.0;
+
+fn main() {
+    let mut rand = Random::from_seed(1);
+    let points: Vec<Point> = (0..COUNT).map(|_| Point::new(rand.float(0.0, 800.0), rand.float(0.0, 800.0))).collect();
+    let queries: Vec<Point> = (0..1000).map(|_| Point::new(rand.float(0.0, 800.0), rand.float(0.0, 800.0))).collect();
+    println!("{} points, {} queries", COUNT, queries.len());
+
+    println!("\nall pairs within {}", RADIUS);
+    let brute = time("brute force", || {
+        let mut count = 0;
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                if dist(&points[i], &points[j]) <= RADIUS {
+                    count += 1;
+                }
+            }
+        }
+        count
+    });
+    let hashed = time("spatial hash, build and query", || SpatialHash::from_points(&points, RADIUS).pairs(RADIUS).len());
+    assert_eq!(brute, hashed);
+
+    println!("\nneighbors within {}", RADIUS);
+    let brute = time("brute force", || {
+        queries.iter().fold(0, |sum, q| sum + points.iter().filter(|p| dist(p, q) <= RADIUS).count())
+    });
+    let hash = SpatialHash::from_points(&points, RADIUS);
+    let hashed = time("spatial hash", || queries.iter().fold(0, |sum, q| sum + hash.neighbors(q, RADIUS).len()));
+    let tree = KdTree::new(&points);
+    let treed = time("kd-tree", || queries.iter().fold(0, |sum, q| sum + tree.within(q, RADIUS).len()));
+    assert_eq!(brute, hashed);
+    assert_eq!(brute, treed);
+
+    println!("\nnearest neighbor");
+    let brute = time("brute force", || {
+        queries.iter().map(|q| {
+            (0..points.len()).min_by(|&a, &b| dist(&points[a], q).partial_cmp(&dist(&points[b], q)).unwrap()).unwrap()
+        }).collect::<Vec<usize>>()
+    });
+    time("kd-tree, build", || KdTree::new(&points).len());
+    let treed = time("kd-tree, query", || queries.iter().map(|q| tree.nearest(q).unwrap()).collect::<Vec<usize>>());
+    assert_eq!(brute, treed);
+}
+
+fn time<T, F: FnMut() -> T>(label: &str, mut f: F) -> T {
+    let start = Instant::now();
+    let result = f();
+    println!("  {:<30} {:>8.2} ms", label, millis(start.elapsed()));
+    result
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1000.0 + d.subsec_nanos() as f64 / 1e6
+}
@@ -1,11 +1,17 @@
 extern crate cairo;
-extern crate rand;
 
 use self::cairo::{Context, ImageSurface, Format};
 use std::fs::File;
 use color::Color;
+use geom::{ fractal_line_points, splat_points };
+use geom::bezier::{ CubicBezier, QuadBezier };
+use geom::corner::{ CornerSize, CornerStyle, corners };
 use geom::point::Point;
-use math::{ clamp, PI, TWO_PI, HALF_PI };
+use geom::polygon::Polygon;
+use geom::spline::{ CatmullRom, catmull_rom_beziers, bspline_beziers };
+use geom::voronoi::Voronoi;
+use math::{ PI, TWO_PI, HALF_PI };
+use random::Random;
 
 pub struct Canvas {
     pub width: f64,
@@ -64,6 +70,10 @@ pub trait BitContext {
     fn fill_path(&self, points: &[Point]);
     fn stroke_path(&self, points: &[Point], close: bool);
 
+    fn poly(&self, polygon: &Polygon);
+    fn fill_poly(&self, polygon: &Polygon);
+    fn stroke_poly(&self, polygon: &Polygon);
+
 	fn polygon(&self, x: f64, y: f64, r: f64, sides: i32, rotation: f64);
 	fn stroke_polygon(&self, x: f64, y: f64, r: f64, sides: i32, rotation: f64);
 	fn fill_polygon(&self, x: f64, y: f64, r: f64, sides: i32, rotation: f64);
@@ -72,6 +82,16 @@ pub trait BitContext {
 	fn stroke_star(&self, x: f64, y: f64, r0: f64, r1: f64, points: i32, rotation: f64);
 	fn fill_star(&self, x: f64, y: f64, r0: f64, r1: f64, points: i32, rotation: f64);
 
+    fn corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle);
+    fn stroke_corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle);
+    fn fill_corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle);
+    fn round_path(&self, points: &[Point], r: f64);
+    fn stroke_round_path(&self, points: &[Point], r: f64);
+    fn fill_round_path(&self, points: &[Point], r: f64);
+    fn round_polygon(&self, polygon: &Polygon, r: f64);
+    fn stroke_round_polygon(&self, polygon: &Polygon, r: f64);
+    fn fill_round_polygon(&self, polygon: &Polygon, r: f64);
+
     fn splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64);
     fn stroke_splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64);
     fn fill_splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64);
@@ -84,6 +104,7 @@ pub trait BitContext {
     fn stroke_heart(&self, x: f64, y: f64, w: f64, h: f64, r: f64);
 
     fn grid(&self, x: f64, y: f64, w: f64, h: f64, xres: f64, yres: f64);
+    fn grid_offset(&self, x: f64, y: f64, w: f64, h: f64, xres: f64, yres: f64, xoffset: f64, yoffset: f64);
 
     fn points(&self, points: &[Point], radius: f64);
 
@@ -95,6 +116,19 @@ pub trait BitContext {
     fn multi_loop(&self, points: &[Point]);
     fn fill_multi_loop(&self, points: &[Point]);
     fn stroke_multi_loop(&self, points: &[Point]);
+    fn catmull_rom(&self, points: &[Point], kind: CatmullRom, closed: bool);
+    fn stroke_catmull_rom(&self, points: &[Point], kind: CatmullRom, closed: bool);
+    fn fill_catmull_rom(&self, points: &[Point], kind: CatmullRom);
+    fn bspline(&self, points: &[Point], closed: bool);
+    fn stroke_bspline(&self, points: &[Point], closed: bool);
+    fn fill_bspline(&self, points: &[Point]);
+    fn beziers(&self, beziers: &[[Point; 4]], closed: bool);
+    fn cubic_bezier(&self, curve: &CubicBezier);
+    fn stroke_cubic_bezier(&self, curve: &CubicBezier);
+    fn quad_bezier(&self, curve: &QuadBezier);
+    fn stroke_quad_bezier(&self, curve: &QuadBezier);
+    fn stroke_voronoi(&self, voronoi: &Voronoi);
+    fn fill_voronoi(&self, voronoi: &Voronoi, color: &dyn Fn(usize, &Point, &Polygon) -> Color);
 }
 
 impl BitContext for Context{
@@ -203,6 +237,21 @@ impl BitContext for Context{
         self.stroke();
     }
 
+    fn poly(&self, polygon: &Polygon) {
+        self.path(&polygon.points);
+        self.close_path();
+    }
+
+    fn fill_poly(&self, polygon: &Polygon) {
+        self.poly(polygon);
+        self.fill();
+    }
+
+    fn stroke_poly(&self, polygon: &Polygon) {
+        self.poly(polygon);
+        self.stroke();
+    }
+
 	fn polygon(&self, x: f64, y: f64, r: f64, sides: i32, rotation: f64) {
         self.save();
         self.translate(x, y);
@@ -252,35 +301,80 @@ impl BitContext for Context{
         self.fill();
     }
 
-    fn splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64) {
-        let mut points: Vec<Point> = Vec::new();
-        let slice = TWO_PI / (num_nodes * 2) as f64;
-        let mut angle = 0.0;
-        let curve = 0.3;
-        let radius_range = radius - inner_radius;
-        let variation = clamp(variation, 0.0, 1.0);
-        for _i in 0..num_nodes {
-            let radius = radius + variation * (rand::random::<f64>() * radius_range * 2.0 - radius_range);
-            let radius_range = radius - inner_radius;
-            points.push(make_point(angle - slice * (1.0 + curve), inner_radius));
-            points.push(make_point(angle + slice * curve, inner_radius));
-            points.push(make_point(angle - slice * curve, inner_radius + radius_range * 0.8));
-            points.push(make_point(angle + slice / 2.0, radius));
-            points.push(make_point(angle + slice * (1.0 + curve), inner_radius + radius_range * 0.8));
-            angle += slice * 2.0;
-        }
-
-        self.save();
-        self.translate(x, y);
-        self.multi_loop(&points);
-        self.restore();
-        
-        fn make_point(angle: f64, radius: f64) -> Point {
-           Point {
-                x: angle.cos() * radius, 
-                y: angle.sin() * radius
+    fn corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle) {
+        for (point, corner) in points.iter().zip(corners(points, size).iter()) {
+            match *corner {
+                None => self.line_to(point.x, point.y),
+                Some(ref c) => {
+                    self.line_to(c.start.x, c.start.y);
+                    match style {
+                        CornerStyle::Round => {
+                            if c.negative {
+                                self.arc_negative(c.center.x, c.center.y, c.radius, c.start_angle, c.end_angle);
+                            }
+                            else {
+                                self.arc(c.center.x, c.center.y, c.radius, c.start_angle, c.end_angle);
+                            }
+                        }
+                        CornerStyle::Chamfer => {
+                            self.line_to(c.end.x, c.end.y);
+                        }
+                        CornerStyle::Inverted => {
+                            let notch = c.inverted();
+                            if notch.negative {
+                                self.arc_negative(notch.center.x, notch.center.y, notch.radius, notch.start_angle, notch.end_angle);
+                            }
+                            else {
+                                self.arc(notch.center.x, notch.center.y, notch.radius, notch.start_angle, notch.end_angle);
+                            }
+                        }
+                    }
+                }
             }
         }
+        self.close_path();
+    }
+
+    fn stroke_corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle) {
+        self.corner_path(points, size, style);
+        self.stroke();
+    }
+
+    fn fill_corner_path(&self, points: &[Point], size: CornerSize, style: CornerStyle) {
+        self.corner_path(points, size, style);
+        self.fill();
+    }
+
+    fn round_path(&self, points: &[Point], r: f64) {
+        self.corner_path(points, CornerSize::Radius(r), CornerStyle::Round);
+    }
+
+    fn stroke_round_path(&self, points: &[Point], r: f64) {
+        self.round_path(points, r);
+        self.stroke();
+    }
+
+    fn fill_round_path(&self, points: &[Point], r: f64) {
+        self.round_path(points, r);
+        self.fill();
+    }
+
+    fn round_polygon(&self, polygon: &Polygon, r: f64) {
+        self.round_path(&polygon.points, r);
+    }
+
+    fn stroke_round_polygon(&self, polygon: &Polygon, r: f64) {
+        self.round_polygon(polygon, r);
+        self.stroke();
+    }
+
+    fn fill_round_polygon(&self, polygon: &Polygon, r: f64) {
+        self.round_polygon(polygon, r);
+        self.fill();
+    }
+
+    fn splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64) {
+        self.multi_loop(&splat_points(x, y, num_nodes, radius, inner_radius, variation, &mut Random::new()));
     }
 
     fn stroke_splat(&self, x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64) {
@@ -294,27 +388,9 @@ impl BitContext for Context{
     }
 
     fn fractal_line(&self, x1: f64, y1: f64, x2: f64, y2: f64, roughness: f64, iterations: i32) {
-        let dx = x2 - x1;
-        let dy = y2 - y1;
-        let mut offset = (dx * dx + dy * dy).sqrt() * 0.15;
-
-        let mut path: Vec<Point> = Vec::new();
-        path.push(Point::new(x1, y1));
-        path.push(Point::new(x2, y2));
-        for _i in 0..iterations {
-            let mut new_path: Vec<Point> = Vec::new();
-            for (j, point) in path.iter().enumerate() {
-                new_path.push(Point::new(point.x, point.y));
-                if j < path.len() - 1 {
-                    let x = (point.x + path[j + 1].x) / 2.0 + rand::random::<f64>() * offset * 2.0 - offset;
-                    let y = (point.y + path[j + 1].y) / 2.0 + rand::random::<f64>() * offset * 2.0 - offset;
-                    new_path.push(Point::new(x, y));
-                }
-            }
-            offset *= roughness;
-            path = new_path;
-        }
-        self.path(&path);
+        let p1 = Point::new(x1, y1);
+        let p2 = Point::new(x2, y2);
+        self.path(&fractal_line_points(p1, p2, roughness, iterations, &mut Random::new()));
     }
 
     fn stroke_fractal_line(&self, x1: f64, y1: f64, x2: f64, y2: f64, roughness: f64, iterations: i32) {
@@ -426,9 +502,90 @@ impl BitContext for Context{
         self.stroke();
     }
 
+    fn catmull_rom(&self, points: &[Point], kind: CatmullRom, closed: bool) {
+        self.beziers(&catmull_rom_beziers(points, kind, closed), closed);
+    }
+
+    fn stroke_catmull_rom(&self, points: &[Point], kind: CatmullRom, closed: bool) {
+        self.catmull_rom(points, kind, closed);
+        self.stroke();
+    }
+
+    fn fill_catmull_rom(&self, points: &[Point], kind: CatmullRom) {
+        self.catmull_rom(points, kind, true);
+        self.fill();
+    }
+
+    fn bspline(&self, points: &[Point], closed: bool) {
+        self.beziers(&bspline_beziers(points, closed), closed);
+    }
+
+    fn stroke_bspline(&self, points: &[Point], closed: bool) {
+        self.bspline(points, closed);
+        self.stroke();
+    }
+
+    fn fill_bspline(&self, points: &[Point]) {
+        self.bspline(points, true);
+        self.fill();
+    }
+
+    fn beziers(&self, beziers: &[[Point; 4]], closed: bool) {
+        if beziers.is_empty() {
+            return;
+        }
+        self.move_to(beziers[0][0].x, beziers[0][0].y);
+        for b in beziers.iter() {
+            self.curve_to(b[1].x, b[1].y, b[2].x, b[2].y, b[3].x, b[3].y);
+        }
+        if closed {
+            self.close_path();
+        }
+    }
+
+    fn cubic_bezier(&self, curve: &CubicBezier) {
+        self.move_to(curve.p0.x, curve.p0.y);
+        self.curve_to(curve.p1.x, curve.p1.y, curve.p2.x, curve.p2.y, curve.p3.x, curve.p3.y);
+    }
+
+    fn stroke_cubic_bezier(&self, curve: &CubicBezier) {
+        self.cubic_bezier(curve);
+        self.stroke();
+    }
+
+    fn quad_bezier(&self, curve: &QuadBezier) {
+        self.cubic_bezier(&curve.to_cubic());
+    }
+
+    fn stroke_quad_bezier(&self, curve: &QuadBezier) {
+        self.quad_bezier(curve);
+        self.stroke();
+    }
+
+    fn stroke_voronoi(&self, voronoi: &Voronoi) {
+        for cell in voronoi.cells.iter().filter(|c| !c.is_empty()) {
+            self.new_sub_path();
+            self.poly(cell);
+        }
+        self.stroke();
+    }
+
+    fn fill_voronoi(&self, voronoi: &Voronoi, color: &dyn Fn(usize, &Point, &Polygon) -> Color) {
+        // the color function is given the cell index, its site and its outline.
+        for (i, cell) in voronoi.cells.iter().enumerate().filter(|&(_, c)| !c.is_empty()) {
+            self.set_source_color(&color(i, &voronoi.sites[i], cell));
+            self.fill_poly(cell);
+        }
+    }
+
     fn grid(&self, x: f64, y: f64, w: f64, h: f64, xres: f64, yres: f64) {
-        let mut xx = x;
-        let mut yy = y;
+        self.grid_offset(x, y, w, h, xres, yres, 0.0, 0.0);
+    }
+
+    fn grid_offset(&self, x: f64, y: f64, w: f64, h: f64, xres: f64, yres: f64, xoffset: f64, yoffset: f64) {
+        // offsets wrap into 0..res so lines always start inside the region.
+        let mut xx = x + (xoffset % xres + xres) % xres;
+        let mut yy = y + (yoffset % yres + yres) % yres;
         while xx <= x + w {
             self.move_to(xx, y);
             self.line_to(xx, y + h);
@@ -33,7 +33,10 @@ impl Color {
     }
 
     pub fn random_rgb() -> Color {
-        let mut rand = Random::new();
+        Color::random_rgb_with(&mut Random::new())
+    }
+
+    pub fn random_rgb_with(rand: &mut Random) -> Color {
         let r = rand.float(0.0, 1.0); 
         let g = rand.float(0.0, 1.0); 
         let b = rand.float(0.0, 1.0); 
@@ -59,7 +62,10 @@ impl Color {
     }
 
     pub fn random_hsv(hmin: f64, hmax: f64, smin: f64, smax: f64, vmin: f64, vmax: f64) -> Color {
-        let mut rand = Random::new();
+        Color::random_hsv_with(hmin, hmax, smin, smax, vmin, vmax, &mut Random::new())
+    }
+
+    pub fn random_hsv_with(hmin: f64, hmax: f64, smin: f64, smax: f64, vmin: f64, vmax: f64, rand: &mut Random) -> Color {
         Color::hsv(rand.float(hmin, hmax), rand.float(smin, smax), rand.float(vmin, vmax))
     }
 
@@ -76,7 +82,14 @@ impl Color {
     }
 
     pub fn random_grey_range(min: f64, max: f64) -> Color {
-        let mut rand = Random::new();
+        Color::random_grey_range_with(min, max, &mut Random::new())
+    }
+
+    pub fn random_grey_with(rand: &mut Random) -> Color {
+        Color::random_grey_range_with(0.0, 1.0, rand)
+    }
+
+    pub fn random_grey_range_with(min: f64, max: f64, rand: &mut Random) -> Color {
         Color::grey(rand.float(min, max))
     }
 
@@ -366,6 +379,25 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_seeded_randoms() {
+        let mut rand0 = Random::from_seed(42);
+        let mut rand1 = Random::from_seed(42);
+
+        let c0 = Color::random_rgb_with(&mut rand0);
+        let c1 = Color::random_rgb_with(&mut rand1);
+        assert_eq!((c0.r, c0.g, c0.b), (c1.r, c1.g, c1.b));
+
+        let c0 = Color::random_hsv_with(0.0, 360.0, 0.5, 1.0, 0.5, 1.0, &mut rand0);
+        let c1 = Color::random_hsv_with(0.0, 360.0, 0.5, 1.0, 0.5, 1.0, &mut rand1);
+        assert_eq!((c0.r, c0.g, c0.b), (c1.r, c1.g, c1.b));
+
+        let c0 = Color::random_grey_range_with(0.25, 0.35, &mut rand0);
+        let c1 = Color::random_grey_range_with(0.25, 0.35, &mut rand1);
+        assert!(c0.r >= 0.25 && c0.r <= 0.35);
+        assert_eq!(c0.r, c1.r);
+    }
+
 
     #[test]
     fn test_from_string() {
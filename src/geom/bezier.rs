@@ -0,0 +1,528 @@
+use super::point::Point;
+use super::rect::Rect;
+use super::{ bezier_point, quadratic_point, dist_to_segment };
+use math::PI;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CubicBezier {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+}
+
+impl CubicBezier {
+    pub fn new(p0: Point, p1: Point, p2: Point, p3: Point) -> CubicBezier {
+        CubicBezier { p0: p0, p1: p1, p2: p2, p3: p3 }
+    }
+
+    // [start, control0, control1, end], as made by the spline functions.
+    pub fn from_array(points: [Point; 4]) -> CubicBezier {
+        CubicBezier::new(points[0], points[1], points[2], points[3])
+    }
+
+    pub fn to_array(&self) -> [Point; 4] {
+        [self.p0, self.p1, self.p2, self.p3]
+    }
+
+    pub fn point_at(&self, t: f64) -> Point {
+        bezier_point(self.p0, self.p1, self.p2, self.p3, t)
+    }
+
+    pub fn derivative(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        ((self.p1 - self.p0) * (mt * mt) + (self.p2 - self.p1) * (2.0 * mt * t) + (self.p3 - self.p2) * (t * t)) * 3.0
+    }
+
+    pub fn second_derivative(&self, t: f64) -> Point {
+        ((self.p2 - self.p1 * 2.0 + self.p0) * (1.0 - t) + (self.p3 - self.p2 * 2.0 + self.p1) * t) * 6.0
+    }
+
+    // still defined where a control point sits on an end point.
+    pub fn tangent(&self, t: f64) -> Point {
+        let d = self.derivative(t);
+        if d.magnitude() > 1e-12 {
+            return d.normalize();
+        }
+        let ahead = self.point_at((t + 1e-6).min(1.0)) - self.point_at((t - 1e-6).max(0.0));
+        if ahead.magnitude() > 0.0 { ahead.normalize() } else { Point::new(0.0, 0.0) }
+    }
+
+    // a quarter turn clockwise on screen from the tangent.
+    pub fn normal(&self, t: f64) -> Point {
+        self.tangent(t).perpendicular()
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length_within(1e-9 * (1.0 + self.control_length()), 0)
+    }
+
+    // halves the curve until the control polygon is barely longer than the chord,
+    // then takes the average of the two as the length of each piece.
+    fn length_within(&self, tolerance: f64, depth: i32) -> f64 {
+        let chord = self.p0.dist(&self.p3);
+        let control = self.control_length();
+        if control - chord <= tolerance || depth >= 24 {
+            return (chord + control) / 2.0;
+        }
+        let (a, b) = self.split(0.5);
+        a.length_within(tolerance / 2.0, depth + 1) + b.length_within(tolerance / 2.0, depth + 1)
+    }
+
+    fn control_length(&self) -> f64 {
+        self.p0.dist(&self.p1) + self.p1.dist(&self.p2) + self.p2.dist(&self.p3)
+    }
+
+    pub fn split(&self, t: f64) -> (CubicBezier, CubicBezier) {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        let c = self.p2.lerp(&self.p3, t);
+        let ab = a.lerp(&b, t);
+        let bc = b.lerp(&c, t);
+        let p = ab.lerp(&bc, t);
+        (CubicBezier::new(self.p0, a, ab, p), CubicBezier::new(p, bc, c, self.p3))
+    }
+
+    pub fn section(&self, t0: f64, t1: f64) -> CubicBezier {
+        let (_, end) = self.split(t0);
+        if t0 >= 1.0 {
+            return end;
+        }
+        end.split((t1 - t0) / (1.0 - t0)).0
+    }
+
+    // bounds of the curve itself, not its control points.
+    pub fn bounds(&self) -> Rect {
+        let d0 = self.p1 - self.p0;
+        let d1 = self.p2 - self.p1;
+        let d2 = self.p3 - self.p2;
+        let mut ts = vec![0.0, 1.0];
+        ts.extend(solve_quadratic(d0.x - 2.0 * d1.x + d2.x, 2.0 * (d1.x - d0.x), d0.x));
+        ts.extend(solve_quadratic(d0.y - 2.0 * d1.y + d2.y, 2.0 * (d1.y - d0.y), d0.y));
+        let points: Vec<Point> = ts.iter().filter(|t| **t >= 0.0 && **t <= 1.0).map(|t| self.point_at(*t)).collect();
+        Rect::from_points(&points)
+    }
+
+    pub fn closest_t(&self, p: &Point) -> f64 {
+        // coarse samples find the right neighbourhood, newton's method polishes it.
+        let samples = 32;
+        let mut best = 0.0;
+        let mut best_dist = f64::MAX;
+        for i in 0..samples + 1 {
+            let t = i as f64 / samples as f64;
+            let d = self.point_at(t).dist(p);
+            if d < best_dist {
+                best_dist = d;
+                best = t;
+            }
+        }
+        let mut t = best;
+        for _ in 0..8 {
+            let offset = self.point_at(t) - *p;
+            let d1 = self.derivative(t);
+            let d2 = self.second_derivative(t);
+            let denominator = d1.dot(&d1) + offset.dot(&d2);
+            if denominator.abs() < 1e-12 {
+                break;
+            }
+            t = (t - offset.dot(&d1) / denominator).max(0.0).min(1.0);
+        }
+        if self.point_at(t).dist(p) <= best_dist { t } else { best }
+    }
+
+    pub fn closest_point(&self, p: &Point) -> Point {
+        self.point_at(self.closest_t(p))
+    }
+
+    // values of t where the curve crosses the infinite line through a and b, in order.
+    pub fn intersect_line(&self, a: &Point, b: &Point) -> Vec<f64> {
+        let n = (*b - *a).perpendicular();
+        // the curve's distance from the line as a cubic in t.
+        let c0 = (self.p0 - *a).dot(&n);
+        let c1 = (self.p1 - *a).dot(&n);
+        let c2 = (self.p2 - *a).dot(&n);
+        let c3 = (self.p3 - *a).dot(&n);
+        let mut ts: Vec<f64> = solve_cubic(c3 - 3.0 * c2 + 3.0 * c1 - c0,
+                                           3.0 * (c2 - 2.0 * c1 + c0),
+                                           3.0 * (c1 - c0),
+                                           c0)
+            .into_iter()
+            .filter(|t| *t >= -1e-9 && *t <= 1.0 + 1e-9)
+            .map(|t| t.max(0.0).min(1.0))
+            .collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+        ts
+    }
+
+    pub fn intersect_segment(&self, a: &Point, b: &Point) -> Vec<f64> {
+        let d = *b - *a;
+        let len_sq = d.dot(&d);
+        self.intersect_line(a, b)
+            .into_iter()
+            .filter(|t| {
+                let u = (self.point_at(*t) - *a).dot(&d) / len_sq;
+                u >= -1e-9 && u <= 1.0 + 1e-9
+            })
+            .collect()
+    }
+
+    pub fn intersect_cubic(&self, other: &CubicBezier) -> Vec<(f64, f64)> {
+        let mut result: Vec<(f64, f64)> = Vec::new();
+        if !overlaps(&self.bounds(), &other.bounds()) {
+            return result;
+        }
+        // intersect flattened copies, then refine each hit on the real curves.
+        let tolerance = 1e-3 * (1.0 + self.control_length().max(other.control_length()));
+        let a = self.flatten_t(tolerance);
+        let b = other.flatten_t(tolerance);
+        for i in 0..a.len() - 1 {
+            for j in 0..b.len() - 1 {
+                if let Some((u, v)) = segment_params(&a[i].1, &a[i + 1].1, &b[j].1, &b[j + 1].1) {
+                    let s = a[i].0 + (a[i + 1].0 - a[i].0) * u;
+                    let t = b[j].0 + (b[j + 1].0 - b[j].0) * v;
+                    let (s, t) = refine_intersection(self, other, s, t);
+                    if !result.iter().any(|&(rs, rt)| (rs - s).abs() < 1e-6 && (rt - t).abs() < 1e-6) {
+                        result.push((s, t));
+                    }
+                }
+            }
+        }
+        result.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        result
+    }
+
+    // no straight run between the points strays further than tolerance from the curve.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        self.flatten_t(tolerance).into_iter().map(|(_, p)| p).collect()
+    }
+
+    // flattened points paired with their t.
+    fn flatten_t(&self, tolerance: f64) -> Vec<(f64, Point)> {
+        let mut result = vec![(0.0, self.p0)];
+        self.flatten_into(tolerance.max(1e-9), 0.0, 1.0, 0, &mut result);
+        result
+    }
+
+    fn flatten_into(&self, tolerance: f64, t0: f64, t1: f64, depth: i32, result: &mut Vec<(f64, Point)>) {
+        let flatness = dist_to_segment(&self.p1, &self.p0, &self.p3).max(dist_to_segment(&self.p2, &self.p0, &self.p3));
+        if flatness <= tolerance || depth >= 16 {
+            result.push((t1, self.p3));
+            return;
+        }
+        let (a, b) = self.split(0.5);
+        let mid = (t0 + t1) / 2.0;
+        a.flatten_into(tolerance, t0, mid, depth + 1, result);
+        b.flatten_into(tolerance, mid, t1, depth + 1, result);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuadBezier {
+    pub p0: Point,
+    pub p1: Point,
+    pub p2: Point,
+}
+
+impl QuadBezier {
+    pub fn new(p0: Point, p1: Point, p2: Point) -> QuadBezier {
+        QuadBezier { p0: p0, p1: p1, p2: p2 }
+    }
+
+    // anything not implemented directly here goes through this.
+    pub fn to_cubic(&self) -> CubicBezier {
+        CubicBezier::new(self.p0,
+                         self.p0.lerp(&self.p1, 2.0 / 3.0),
+                         self.p2.lerp(&self.p1, 2.0 / 3.0),
+                         self.p2)
+    }
+
+    pub fn point_at(&self, t: f64) -> Point {
+        quadratic_point(self.p0, self.p1, self.p2, t)
+    }
+
+    pub fn derivative(&self, t: f64) -> Point {
+        ((self.p1 - self.p0) * (1.0 - t) + (self.p2 - self.p1) * t) * 2.0
+    }
+
+    pub fn tangent(&self, t: f64) -> Point {
+        self.to_cubic().tangent(t)
+    }
+
+    pub fn normal(&self, t: f64) -> Point {
+        self.tangent(t).perpendicular()
+    }
+
+    pub fn length(&self) -> f64 {
+        self.to_cubic().length()
+    }
+
+    pub fn split(&self, t: f64) -> (QuadBezier, QuadBezier) {
+        let a = self.p0.lerp(&self.p1, t);
+        let b = self.p1.lerp(&self.p2, t);
+        let p = a.lerp(&b, t);
+        (QuadBezier::new(self.p0, a, p), QuadBezier::new(p, b, self.p2))
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.to_cubic().bounds()
+    }
+
+    pub fn closest_t(&self, p: &Point) -> f64 {
+        self.to_cubic().closest_t(p)
+    }
+
+    pub fn closest_point(&self, p: &Point) -> Point {
+        self.point_at(self.closest_t(p))
+    }
+
+    pub fn intersect_line(&self, a: &Point, b: &Point) -> Vec<f64> {
+        self.to_cubic().intersect_line(a, b)
+    }
+
+    pub fn intersect_segment(&self, a: &Point, b: &Point) -> Vec<f64> {
+        self.to_cubic().intersect_segment(a, b)
+    }
+
+    // convert quadratics with to_cubic to intersect them with each other.
+    pub fn intersect_cubic(&self, other: &CubicBezier) -> Vec<(f64, f64)> {
+        self.to_cubic().intersect_cubic(other)
+    }
+
+    pub fn flatten(&self, tolerance: f64) -> Vec<Point> {
+        self.to_cubic().flatten(tolerance)
+    }
+}
+
+// a new subpath starts wherever a curve doesn't begin where the last one ended.
+pub fn svg_path(curves: &[CubicBezier]) -> String {
+    let mut parts = Vec::new();
+    let mut end: Option<Point> = None;
+    for c in curves.iter() {
+        if end != Some(c.p0) {
+            parts.push(format!("M{} {}", c.p0.x, c.p0.y));
+        }
+        parts.push(format!("C{} {} {} {} {} {}", c.p1.x, c.p1.y, c.p2.x, c.p2.y, c.p3.x, c.p3.y));
+        end = Some(c.p3);
+    }
+    parts.join(" ")
+}
+
+// newton's method on a(s) - b(t) = 0, keeping the starting guess if it wanders off.
+fn refine_intersection(a: &CubicBezier, b: &CubicBezier, s: f64, t: f64) -> (f64, f64) {
+    let (mut s1, mut t1) = (s, t);
+    for _ in 0..8 {
+        let f = a.point_at(s1) - b.point_at(t1);
+        let da = a.derivative(s1);
+        let db = -b.derivative(t1);
+        let det = da.cross(&db);
+        if det.abs() < 1e-12 {
+            break;
+        }
+        s1 -= f.cross(&db) / det;
+        t1 -= da.cross(&f) / det;
+    }
+    if s1 >= 0.0 && s1 <= 1.0 && t1 >= 0.0 && t1 <= 1.0 &&
+       a.point_at(s1).dist(&b.point_at(t1)) <= a.point_at(s).dist(&b.point_at(t)) {
+        (s1, t1)
+    }
+    else {
+        (s, t)
+    }
+}
+
+// the parameters along p0-p1 and p2-p3 where they cross.
+fn segment_params(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> Option<(f64, f64)> {
+    let r = *p1 - *p0;
+    let s = *p3 - *p2;
+    let d = r.cross(&s);
+    if d == 0.0 {
+        return None;
+    }
+    let t = (*p2 - *p0).cross(&s) / d;
+    let u = (*p2 - *p0).cross(&r) / d;
+    if t >= 0.0 && t <= 1.0 && u >= 0.0 && u <= 1.0 { Some((t, u)) } else { None }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x <= b.x + b.w && b.x <= a.x + a.w && a.y <= b.y + b.h && b.y <= a.y + a.h
+}
+
+// real roots of a t^2 + b t + c.
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs());
+    if scale == 0.0 {
+        return Vec::new();
+    }
+    if a.abs() < 1e-12 * scale {
+        return if b.abs() < 1e-12 * scale { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    vec![(-b + root) / (2.0 * a), (-b - root) / (2.0 * a)]
+}
+
+// real roots of a t^3 + b t^2 + c t + d, polished with a few newton steps.
+fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs());
+    if scale == 0.0 {
+        return Vec::new();
+    }
+    if a.abs() < 1e-12 * scale {
+        return solve_quadratic(b, c, d);
+    }
+    let (p, q, r) = (b / a, c / a, d / a);
+    let shift = p / 3.0;
+    let qq = (3.0 * q - p * p) / 9.0;
+    let rr = (9.0 * p * q - 27.0 * r - 2.0 * p * p * p) / 54.0;
+    let discriminant = qq * qq * qq + rr * rr;
+    let roots = if discriminant >= 0.0 {
+        let root = discriminant.sqrt();
+        let s = (rr + root).cbrt();
+        let t = (rr - root).cbrt();
+        vec![s + t - shift, -(s + t) / 2.0 - shift]
+    }
+    else {
+        let theta = (rr / (-qq * qq * qq).sqrt()).max(-1.0).min(1.0).acos();
+        let m = 2.0 * (-qq).sqrt();
+        (0..3).map(|k| m * ((theta + 2.0 * PI * k as f64) / 3.0).cos() - shift).collect()
+    };
+    roots.into_iter()
+        .map(|mut x| {
+            for _ in 0..4 {
+                let f = ((a * x + b) * x + c) * x + d;
+                let df = (3.0 * a * x + 2.0 * b) * x + c;
+                if df == 0.0 {
+                    break;
+                }
+                x -= f / df;
+            }
+            x
+        })
+        .filter(|x| {
+            let f = ((a * x + b) * x + c) * x + d;
+            f.abs() <= 1e-8 * scale * (1.0 + x.abs()).powi(3)
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    fn arch() -> CubicBezier {
+        CubicBezier::new(Point::new(0.0, 0.0), Point::new(0.0, 100.0), Point::new(100.0, 100.0), Point::new(100.0, 0.0))
+    }
+
+    fn line() -> CubicBezier {
+        CubicBezier::new(Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(20.0, 0.0), Point::new(30.0, 0.0))
+    }
+
+    #[test]
+    fn test_derivatives() {
+        let c = arch();
+        assert_eq!(c.derivative(0.0), Point::new(0.0, 300.0));
+        assert_eq!(c.tangent(0.5), Point::new(1.0, 0.0));
+        let n = c.normal(0.5);
+        assert!(approx_eq(n.x, 0.0) && approx_eq(n.y, 1.0));
+        // a control point on the end point leaves the derivative zero there.
+        let flat = CubicBezier::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0), Point::new(10.0, 10.0), Point::new(20.0, 10.0));
+        let t = flat.tangent(0.0);
+        assert!(approx_eq(t.x, t.y));
+    }
+
+    #[test]
+    fn test_length() {
+        assert!(approx_eq(line().length(), 30.0));
+        let quarter = 0.5522847498 * 100.0;
+        let arc = CubicBezier::new(Point::new(100.0, 0.0), Point::new(100.0, quarter), Point::new(quarter, 100.0), Point::new(0.0, 100.0));
+        assert!((arc.length() - 50.0 * PI).abs() < 0.05);
+        let q = QuadBezier::new(Point::new(0.0, 0.0), Point::new(15.0, 0.0), Point::new(30.0, 0.0));
+        assert!(approx_eq(q.length(), 30.0));
+    }
+
+    #[test]
+    fn test_split() {
+        let c = arch();
+        let (a, b) = c.split(0.3);
+        assert_eq!(a.p3, b.p0);
+        for i in 0..11 {
+            let t = i as f64 / 10.0;
+            let p = a.point_at(t);
+            let q = c.point_at(t * 0.3);
+            assert!(approx_eq(p.x, q.x) && approx_eq(p.y, q.y));
+        }
+        let s = c.section(0.25, 0.75);
+        assert!(approx_eq(s.p0.x, c.point_at(0.25).x));
+        assert!(approx_eq(s.p3.x, c.point_at(0.75).x));
+        let (qa, qb) = QuadBezier::new(Point::new(0.0, 0.0), Point::new(50.0, 100.0), Point::new(100.0, 0.0)).split(0.5);
+        assert_eq!(qa.p2, Point::new(50.0, 50.0));
+        assert_eq!(qb.p0, Point::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = arch().bounds();
+        assert!(approx_eq(b.x, 0.0) && approx_eq(b.w, 100.0));
+        assert!(approx_eq(b.y, 0.0) && approx_eq(b.h, 75.0));
+        let q = QuadBezier::new(Point::new(0.0, 0.0), Point::new(50.0, 100.0), Point::new(100.0, 0.0)).bounds();
+        assert!(approx_eq(q.h, 50.0));
+    }
+
+    #[test]
+    fn test_closest() {
+        let c = arch();
+        assert!(approx_eq(c.closest_t(&Point::new(50.0, 200.0)), 0.5));
+        let p = c.closest_point(&Point::new(-10.0, -10.0));
+        assert!(approx_eq(p.x, 0.0) && approx_eq(p.y, 0.0));
+    }
+
+    #[test]
+    fn test_intersections() {
+        let c = arch();
+        let ts = c.intersect_line(&Point::new(0.0, 50.0), &Point::new(1.0, 50.0));
+        assert_eq!(ts.len(), 2);
+        for t in ts.iter() {
+            assert!(approx_eq(c.point_at(*t).y, 50.0));
+        }
+        assert!(approx_eq(ts[0] + ts[1], 1.0));
+        assert_eq!(c.intersect_segment(&Point::new(-10.0, 50.0), &Point::new(50.0, 50.0)).len(), 1);
+        assert!(c.intersect_line(&Point::new(0.0, 200.0), &Point::new(1.0, 200.0)).is_empty());
+
+        let flipped = CubicBezier::new(Point::new(0.0, 75.0), Point::new(0.0, -25.0), Point::new(100.0, -25.0), Point::new(100.0, 75.0));
+        let hits = c.intersect_cubic(&flipped);
+        assert_eq!(hits.len(), 2);
+        for &(s, t) in hits.iter() {
+            let p = c.point_at(s);
+            let q = flipped.point_at(t);
+            assert!(p.dist(&q) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_svg_path() {
+        let a = line();
+        let b = CubicBezier::new(a.p3, Point::new(40.0, 0.0), Point::new(50.0, 10.0), Point::new(50.0, 20.0));
+        assert_eq!(svg_path(&[a, b]), "M0 0 C10 0 20 0 30 0 C40 0 50 10 50 20");
+        assert_eq!(svg_path(&[b, a]), "M30 0 C40 0 50 10 50 20 M0 0 C10 0 20 0 30 0");
+    }
+
+    #[test]
+    fn test_flatten() {
+        assert_eq!(line().flatten(0.1).len(), 2);
+        let c = arch();
+        let coarse = c.flatten(1.0);
+        let fine = c.flatten(0.01);
+        assert!(fine.len() > coarse.len());
+        assert_eq!(fine[0], c.p0);
+        assert_eq!(fine[fine.len() - 1], c.p3);
+        for p in fine.iter() {
+            assert!(c.closest_point(p).dist(p) < 1e-6);
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use super::point::Point;
 use super::dist;
+use super::line::Linear;
+use super::rect::Rect;
+use math::{ PI, TWO_PI };
+use random::Random;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Circle {
     pub x: f64,
     pub y: f64,
@@ -8,11 +13,47 @@ pub struct Circle {
 }
 
 impl Circle {
+    pub fn new(x: f64, y: f64, r: f64) -> Circle {
+        Circle { x: x, y: y, r: r }
+    }
+
+    pub fn through_points(p0: &Point, p1: &Point, p2: &Point) -> Option<Circle> {
+        let b = *p1 - *p0;
+        let c = *p2 - *p0;
+        let d = 2.0 * b.cross(&c);
+        if d.abs() <= 1e-12 * b.dot(&b).max(c.dot(&c)) {
+            return None;
+        }
+        let b_sq = b.dot(&b);
+        let c_sq = c.dot(&c);
+        let center = *p0 + Point::new(c.y * b_sq - b.y * c_sq, b.x * c_sq - c.x * b_sq) / d;
+        Some(Circle::new(center.x, center.y, center.dist(p0)))
+    }
+
+    pub fn area(&self) -> f64 {
+        PI * self.r * self.r
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        TWO_PI * self.r
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(self.x - self.r, self.y - self.r, self.r * 2.0, self.r * 2.0)
+    }
+
+    // spread evenly over the area, not bunched at the center.
+    pub fn random_point(&self, rand: &mut Random) -> Point {
+        let angle = rand.float(0.0, TWO_PI);
+        let r = self.r * rand.float(0.0, 1.0).sqrt();
+        self.center() + Point::from_polar(angle, r)
+    }
+
     pub fn contains_point(&self, p: &Point) -> bool {
         dist(p, &self.center()) <= self.r
     }
 
-    pub fn intersects_circle(&self, other: Circle) -> bool {
+    pub fn intersects_circle(&self, other: &Circle) -> bool {
         let d = dist(&self.center(), &other.center());
         d < self.r + other.r
     }
@@ -20,4 +61,188 @@ impl Circle {
     pub fn center(&self) -> Point {
         Point::new(self.x, self.y)
     }
+
+    // seen from the center, the first is anticlockwise on screen from p. None if p is inside.
+    pub fn tangent_points(&self, p: &Point) -> Option<(Point, Point)> {
+        let d = dist(p, &self.center());
+        if d < self.r || d == 0.0 {
+            return None;
+        }
+        let base = self.center().angle_to(p);
+        let spread = (self.r / d).min(1.0).acos();
+        Some((self.point_at_angle(base - spread), self.point_at_angle(base + spread)))
+    }
+
+    // identical circles share every point and return none.
+    pub fn intersect_circle(&self, other: &Circle) -> Vec<Point> {
+        let offset = other.center() - self.center();
+        let d = offset.magnitude();
+        let epsilon = 1e-9 * (self.r + other.r + d);
+        if d < epsilon || d > self.r + other.r + epsilon || d < (self.r - other.r).abs() - epsilon {
+            return Vec::new();
+        }
+        // distance along the line of centers to the chord through both points.
+        let a = (d * d + self.r * self.r - other.r * other.r) / (2.0 * d);
+        let h = (self.r * self.r - a * a).max(0.0).sqrt();
+        let mid = self.center() + offset * (a / d);
+        if h <= epsilon {
+            return vec![mid];
+        }
+        let across = offset.perpendicular() * (h / d);
+        vec![mid - across, mid + across]
+    }
+
+    // in order along the line.
+    pub fn intersect_line<T: Linear>(&self, line: &T) -> Vec<Point> {
+        line.intersect_circle(self).into_iter().map(|(_, p)| p).collect()
+    }
+
+    // as a belt around two pulleys: the tangent point on self, then on other.
+    pub fn external_tangents(&self, other: &Circle) -> Vec<(Point, Point)> {
+        self.common_tangents(other, 1.0)
+    }
+
+    // as a crossed belt: the tangent point on self, then on other.
+    pub fn internal_tangents(&self, other: &Circle) -> Vec<(Point, Point)> {
+        self.common_tangents(other, -1.0)
+    }
+
+    // `side` is 1 for external tangents, where both circles sit on the same side of
+    // the line, and -1 for internal ones.
+    fn common_tangents(&self, other: &Circle, side: f64) -> Vec<(Point, Point)> {
+        let offset = other.center() - self.center();
+        let d = offset.magnitude();
+        if d == 0.0 {
+            return Vec::new();
+        }
+        let cos = (self.r - side * other.r) / d;
+        if cos.abs() > 1.0 + 1e-9 {
+            return Vec::new();
+        }
+        let cos = cos.max(-1.0).min(1.0);
+        let sin = (1.0 - cos * cos).sqrt();
+        let v = offset / d;
+        let tangent = |sin: f64| {
+            let n = Point::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+            (self.center() + n * self.r, other.center() + n * (side * other.r))
+        };
+        if sin < 1e-9 {
+            vec![tangent(0.0)]
+        }
+        else {
+            vec![tangent(sin), tangent(-sin)]
+        }
+    }
+
+    pub fn point_at_angle(&self, angle: f64) -> Point {
+        self.center() + Point::from_polar(angle, self.r)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use geom::line::Line;
+
+    fn near(a: &Point, b: &Point) -> bool {
+        approx_eq(a.x, b.x) && approx_eq(a.y, b.y)
+    }
+
+    fn is_tangent(circle: &Circle, p0: &Point, p1: &Point) -> bool {
+        // the radius to a tangent point is square to the tangent line.
+        approx_eq((*p0 - circle.center()).dot(&(*p1 - *p0)), 0.0)
+    }
+
+    #[test]
+    fn test_measurements() {
+        let circle = Circle::new(10.0, 20.0, 5.0);
+        assert!(approx_eq(circle.area(), PI * 25.0));
+        assert!(approx_eq(circle.perimeter(), PI * 10.0));
+        assert_eq!(circle.bounds(), Rect::new(5.0, 15.0, 10.0, 10.0));
+        assert!(near(&circle.point_at_angle(PI / 2.0), &Point::new(10.0, 25.0)));
+        let mut rand = Random::from_seed(2);
+        for _ in 0..100 {
+            assert!(circle.contains_point(&circle.random_point(&mut rand)));
+        }
+    }
+
+    #[test]
+    fn test_through_points() {
+        let circle = Circle::through_points(&Point::new(0.0, 5.0), &Point::new(5.0, 0.0), &Point::new(-5.0, 0.0))
+            .expect("no circle");
+        assert!(approx_eq(circle.x, 0.0) && approx_eq(circle.y, 0.0) && approx_eq(circle.r, 5.0));
+        let circle = Circle::through_points(&Point::new(1.0, 1.0), &Point::new(7.0, 1.0), &Point::new(1.0, 9.0))
+            .expect("no circle");
+        assert!(approx_eq(circle.x, 4.0) && approx_eq(circle.y, 5.0) && approx_eq(circle.r, 5.0));
+        assert_eq!(Circle::through_points(&Point::new(0.0, 0.0), &Point::new(1.0, 1.0), &Point::new(2.0, 2.0)), None);
+    }
+
+    #[test]
+    fn test_tangent_points() {
+        let circle = Circle::new(0.0, 0.0, 5.0);
+        let p = Point::new(10.0, 0.0);
+        let (a, b) = circle.tangent_points(&p).expect("no tangents");
+        assert!(is_tangent(&circle, &a, &p));
+        assert!(is_tangent(&circle, &b, &p));
+        assert!(approx_eq(a.x, 2.5) && a.y < 0.0);
+        assert!(approx_eq(b.x, 2.5) && b.y > 0.0);
+        assert!(circle.tangent_points(&Point::new(1.0, 1.0)).is_none());
+        let (a, b) = circle.tangent_points(&Point::new(0.0, 5.0)).expect("no tangents");
+        assert!(near(&a, &b));
+    }
+
+    #[test]
+    fn test_intersect_circle() {
+        let a = Circle::new(0.0, 0.0, 5.0);
+        let points = a.intersect_circle(&Circle::new(8.0, 0.0, 5.0));
+        assert_eq!(points.len(), 2);
+        assert!(near(&points[0], &Point::new(4.0, -3.0)));
+        assert!(near(&points[1], &Point::new(4.0, 3.0)));
+        assert_eq!(a.intersect_circle(&Circle::new(10.0, 0.0, 5.0)).len(), 1);
+        assert_eq!(a.intersect_circle(&Circle::new(3.0, 0.0, 2.0)).len(), 1);
+        assert!(a.intersect_circle(&Circle::new(20.0, 0.0, 5.0)).is_empty());
+        assert!(a.intersect_circle(&Circle::new(1.0, 0.0, 1.0)).is_empty());
+        assert!(a.intersect_circle(&Circle::new(0.0, 0.0, 5.0)).is_empty());
+        assert!(a.intersects_circle(&Circle::new(8.0, 0.0, 5.0)));
+    }
+
+    #[test]
+    fn test_intersect_line() {
+        let circle = Circle::new(0.0, 0.0, 5.0);
+        let points = circle.intersect_line(&Line::new(Point::new(-10.0, 3.0), Point::new(10.0, 3.0)));
+        assert_eq!(points.len(), 2);
+        assert!(near(&points[0], &Point::new(-4.0, 3.0)));
+        assert!(near(&points[1], &Point::new(4.0, 3.0)));
+    }
+
+    #[test]
+    fn test_common_tangents() {
+        let a = Circle::new(0.0, 0.0, 10.0);
+        let b = Circle::new(50.0, 0.0, 5.0);
+        let external = a.external_tangents(&b);
+        assert_eq!(external.len(), 2);
+        for &(p0, p1) in external.iter() {
+            assert!(approx_eq(p0.dist(&a.center()), 10.0));
+            assert!(approx_eq(p1.dist(&b.center()), 5.0));
+            assert!(is_tangent(&a, &p0, &p1));
+            assert!(is_tangent(&b, &p1, &p0));
+            // both circles on the same side.
+            assert!(p0.y.signum() == p1.y.signum());
+        }
+        let internal = a.internal_tangents(&b);
+        assert_eq!(internal.len(), 2);
+        for &(p0, p1) in internal.iter() {
+            assert!(is_tangent(&a, &p0, &p1));
+            assert!(is_tangent(&b, &p1, &p0));
+            assert!(p0.y.signum() != p1.y.signum());
+        }
+
+        // touching circles share one internal tangent; nested ones have none.
+        assert_eq!(a.internal_tangents(&Circle::new(15.0, 0.0, 5.0)).len(), 1);
+        assert!(a.internal_tangents(&Circle::new(12.0, 0.0, 5.0)).is_empty());
+        assert!(a.external_tangents(&Circle::new(1.0, 0.0, 2.0)).is_empty());
+        assert_eq!(a.external_tangents(&Circle::new(5.0, 0.0, 5.0)).len(), 1);
+    }
 }
@@ -0,0 +1,388 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use super::point::Point;
+use super::polygon::Polygon;
+use super::rect::Rect;
+
+// sutherland-hodgman. the subject may be concave, the clip may wind either way.
+pub fn clip_to_convex(subject: &Polygon, clip: &Polygon) -> Polygon {
+    let sign = clip.signed_area().signum();
+    let mut output = subject.points.clone();
+    for (a, b) in clip.edges() {
+        if output.is_empty() {
+            break;
+        }
+        let input = output;
+        output = Vec::new();
+        let inside = |p: &Point| (b - a).cross(&(*p - a)) * sign >= 0.0;
+        for i in 0..input.len() {
+            let current = input[i];
+            let prev = input[(i + input.len() - 1) % input.len()];
+            if inside(&current) {
+                if !inside(&prev) {
+                    output.push(line_intersection(&prev, &current, &a, &b));
+                }
+                output.push(current);
+            }
+            else if inside(&prev) {
+                output.push(line_intersection(&prev, &current, &a, &b));
+            }
+        }
+    }
+    Polygon::new(output)
+}
+
+pub fn clip_to_rect(subject: &Polygon, rect: &Rect) -> Polygon {
+    clip_to_convex(subject, &Polygon::from_rect(rect))
+}
+
+pub fn intersection(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(a, b, Operation::Intersection)
+}
+
+// if one encloses the other without touching, only the outer one comes back.
+pub fn union(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(a, b, Operation::Union)
+}
+
+// if b sits inside a, gives a clockwise then b anticlockwise as a hole.
+pub fn difference(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    boolean(a, b, Operation::Difference)
+}
+
+pub fn xor(a: &Polygon, b: &Polygon) -> Vec<Polygon> {
+    let mut result = difference(a, b);
+    result.extend(difference(b, a));
+    result
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Operation {
+    Intersection,
+    Union,
+    Difference,
+}
+
+// where a piece of one outline lies against the other polygon.
+#[derive(Clone, Copy, PartialEq)]
+enum Side {
+    Inside,
+    Outside,
+    // along the other outline, running the same way or the opposite way.
+    Same,
+    Opposite,
+}
+
+// both outlines are split wherever they cross or touch, each piece is sorted by where it
+// lies against the other polygon, and the pieces the operation keeps are joined into rings.
+// shared edges and corners need no special treatment, and every point in the result is an
+// input point or a crossing.
+fn boolean(subject: &Polygon, clip: &Polygon, op: Operation) -> Vec<Polygon> {
+    if subject.len() < 3 || clip.len() < 3 {
+        return match op {
+            Operation::Intersection => Vec::new(),
+            Operation::Union => [subject, clip].iter().filter(|p| p.len() >= 3).map(|p| (*p).clone()).collect(),
+            Operation::Difference => if subject.len() >= 3 { vec![subject.clone()] } else { Vec::new() },
+        };
+    }
+    let mut all = subject.points.clone();
+    all.extend(clip.points.iter().cloned());
+    let bounds = Rect::from_points(&all);
+    let eps = bounds.w.max(bounds.h).max(1.0) * 1e-10;
+
+    let a = subject.clockwise();
+    // corners of b that sit on corners of a are moved onto them, so shared corners match exactly.
+    let b = Polygon::new(clip.clockwise().points.iter()
+        .map(|q| *a.points.iter().find(|p| p.dist(q) <= eps).unwrap_or(q))
+        .collect());
+    let (ring_a, ring_b, touching) = split_outlines(&a, &b, eps);
+    if !touching {
+        return no_crossings(subject, clip, op);
+    }
+
+    let mut pieces = Vec::new();
+    for (p0, p1) in Polygon::new(ring_a).edges() {
+        match (classify(&p0, &p1, &b, eps), op) {
+            (Side::Inside, Operation::Intersection) | (Side::Same, Operation::Intersection) |
+            (Side::Outside, Operation::Union) | (Side::Same, Operation::Union) |
+            (Side::Outside, Operation::Difference) | (Side::Opposite, Operation::Difference) => pieces.push((p0, p1)),
+            _ => {}
+        }
+    }
+    // shared edges were taken from a, so b only adds the pieces off a's outline.
+    for (q0, q1) in Polygon::new(ring_b).edges() {
+        match (classify(&q0, &q1, &a, eps), op) {
+            (Side::Inside, Operation::Intersection) | (Side::Outside, Operation::Union) => pieces.push((q0, q1)),
+            (Side::Inside, Operation::Difference) => pieces.push((q1, q0)),
+            _ => {}
+        }
+    }
+    join_pieces(&pieces, eps)
+}
+
+fn no_crossings(a: &Polygon, b: &Polygon, op: Operation) -> Vec<Polygon> {
+    let a_in_b = b.contains_point(&a.points[0]);
+    let b_in_a = a.contains_point(&b.points[0]);
+    match op {
+        Operation::Intersection => {
+            if a_in_b { vec![a.clone()] } else if b_in_a { vec![b.clone()] } else { Vec::new() }
+        }
+        Operation::Union => {
+            if a_in_b { vec![b.clone()] } else if b_in_a { vec![a.clone()] } else { vec![a.clone(), b.clone()] }
+        }
+        Operation::Difference => {
+            if a_in_b { Vec::new() } else if b_in_a { vec![a.clockwise(), b.anticlockwise()] } else { vec![a.clone()] }
+        }
+    }
+}
+
+// the points of both outlines with every crossing, and every corner of one lying along an
+// edge of the other, added in order. also says whether the outlines meet at all.
+fn split_outlines(a: &Polygon, b: &Polygon, eps: f64) -> (Vec<Point>, Vec<Point>, bool) {
+    let mut a_splits: Vec<Vec<(f64, Point)>> = vec![Vec::new(); a.len()];
+    let mut b_splits: Vec<Vec<(f64, Point)>> = vec![Vec::new(); b.len()];
+    for (i, &(p0, p1)) in a.edges().iter().enumerate() {
+        for (j, &(q0, q1)) in b.edges().iter().enumerate() {
+            if let Some(t) = along(&q0, &p0, &p1, eps) {
+                a_splits[i].push((t, q0));
+            }
+            if let Some(t) = along(&p0, &q0, &q1, eps) {
+                b_splits[j].push((t, p0));
+            }
+            // edges crossing clear of any corner.
+            let (s0, s1) = (side(&p0, &q0, &q1), side(&p1, &q0, &q1));
+            let (s2, s3) = (side(&q0, &p0, &p1), side(&q1, &p0, &p1));
+            if s0.abs() > eps && s1.abs() > eps && s2.abs() > eps && s3.abs() > eps &&
+               (s0 > 0.0) != (s1 > 0.0) && (s2 > 0.0) != (s3 > 0.0) {
+                let t = s0 / (s0 - s1);
+                let p = p0.lerp(&p1, t);
+                a_splits[i].push((t, p));
+                b_splits[j].push((s2 / (s2 - s3), p));
+            }
+        }
+    }
+    let touching = a_splits.iter().chain(b_splits.iter()).any(|hits| !hits.is_empty()) ||
+        a.points.iter().any(|p| b.points.contains(p));
+    (splice(&a.points, a_splits), splice(&b.points, b_splits), touching)
+}
+
+// the outline with each edge's splits added after its start point, ordered along the edge.
+fn splice(points: &[Point], splits: Vec<Vec<(f64, Point)>>) -> Vec<Point> {
+    let mut ring: Vec<Point> = Vec::new();
+    for (p, mut hits) in points.iter().zip(splits) {
+        hits.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+        for q in Some(*p).into_iter().chain(hits.into_iter().map(|(_, q)| q)) {
+            if ring.last() != Some(&q) {
+                ring.push(q);
+            }
+        }
+    }
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    ring
+}
+
+fn classify(p0: &Point, p1: &Point, other: &Polygon, eps: f64) -> Side {
+    let mid = p0.lerp(p1, 0.5);
+    for (q0, q1) in other.edges() {
+        let t = (mid - q0).dot(&(q1 - q0)) / (q1 - q0).dot(&(q1 - q0));
+        if (0.0..=1.0).contains(&t) && side(&mid, &q0, &q1).abs() <= eps {
+            return if (*p1 - *p0).dot(&(q1 - q0)) > 0.0 { Side::Same } else { Side::Opposite };
+        }
+    }
+    if other.contains_point(&mid) { Side::Inside } else { Side::Outside }
+}
+
+// joins pieces end to start into closed rings. where rings meet at a corner, taking the
+// sharpest clockwise turn keeps them apart.
+fn join_pieces(pieces: &[(Point, Point)], eps: f64) -> Vec<Polygon> {
+    let key = |p: &Point| (p.x.to_bits(), p.y.to_bits());
+    let mut starts: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        starts.entry(key(&piece.0)).or_default().push(i);
+    }
+    let turn = |from: &(Point, Point), to: &(Point, Point)| {
+        let (d0, d1) = (from.1 - from.0, to.1 - to.0);
+        d0.cross(&d1).atan2(d0.dot(&d1))
+    };
+    let mut used = vec![false; pieces.len()];
+    let mut result = Vec::new();
+    for first in 0..pieces.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut points = vec![pieces[first].0];
+        let mut current = first;
+        let mut closed = false;
+        loop {
+            let end = pieces[current].1;
+            if end == points[0] {
+                closed = true;
+                break;
+            }
+            let next = starts.get(&key(&end)).and_then(|candidates| {
+                candidates.iter().cloned().filter(|&k| !used[k])
+                    .max_by(|&x, &y| turn(&pieces[current], &pieces[x]).partial_cmp(&turn(&pieces[current], &pieces[y])).unwrap_or(Ordering::Equal))
+            });
+            match next {
+                Some(k) => {
+                    used[k] = true;
+                    points.push(end);
+                    current = k;
+                }
+                None => break,
+            }
+        }
+        let points = drop_straight(points, eps);
+        if closed && points.len() >= 3 {
+            result.push(Polygon::new(points));
+        }
+    }
+    result
+}
+
+// removes points partway along a straight run, left where the outlines were split.
+fn drop_straight(mut points: Vec<Point>, eps: f64) -> Vec<Point> {
+    let mut i = 0;
+    let mut kept = 0;
+    while points.len() > 3 && kept < points.len() {
+        let n = points.len();
+        let (prev, p, next) = (points[(i + n - 1) % n], points[i % n], points[(i + 1) % n]);
+        if side(&p, &prev, &next).abs() <= eps && (p - prev).dot(&(next - p)) > 0.0 {
+            points.remove(i % n);
+            kept = 0;
+        }
+        else {
+            i = (i + 1) % n;
+            kept += 1;
+        }
+    }
+    points
+}
+
+// where along a to b the point `p` lies, if it is on the edge and clear of both ends.
+fn along(p: &Point, a: &Point, b: &Point, eps: f64) -> Option<f64> {
+    let t = (*p - *a).dot(&(*b - *a)) / (*b - *a).dot(&(*b - *a));
+    if t > 0.0 && t < 1.0 && side(p, a, b).abs() <= eps && p.dist(a) > eps && p.dist(b) > eps {
+        Some(t)
+    }
+    else {
+        None
+    }
+}
+
+// how far `p` is to the clockwise side of the line from a to b.
+fn side(p: &Point, a: &Point, b: &Point) -> f64 {
+    (*b - *a).cross(&(*p - *a)) / a.dist(b)
+}
+
+fn line_intersection(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> Point {
+    let d = (*p1 - *p0).cross(&(*p3 - *p2));
+    if d == 0.0 {
+        return *p1;
+    }
+    let t = (*p2 - *p0).cross(&(*p3 - *p2)) / d;
+    p0.lerp(p1, t)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    fn total_area(polygons: &[Polygon]) -> f64 {
+        polygons.iter().fold(0.0, |sum, p| sum + p.signed_area().abs())
+    }
+
+    #[test]
+    fn test_clip_to_rect() {
+        let clipped = clip_to_rect(&Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0)), &Rect { x: 50.0, y: 50.0, w: 100.0, h: 100.0 });
+        assert_eq!(clipped.len(), 4);
+        assert!(approx_eq(clipped.area(), 2500.0));
+        let outside = clip_to_rect(&Polygon::from_rect(&Rect::new(0.0, 0.0, 10.0, 10.0)), &Rect { x: 50.0, y: 50.0, w: 100.0, h: 100.0 });
+        assert!(outside.is_empty());
+    }
+
+    #[test]
+    fn test_clip_to_convex_either_winding() {
+        let tri = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(0.0, 100.0)]);
+        let subject = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert!(approx_eq(clip_to_convex(&subject, &tri).area(), 5000.0));
+        assert!(approx_eq(clip_to_convex(&subject, &tri.reversed()).area(), 5000.0));
+    }
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        let b = Polygon::from_rect(&Rect::new(50.0, 50.0, 100.0, 100.0));
+        let i = intersection(&a, &b);
+        assert_eq!(i.len(), 1);
+        assert!(approx_eq(total_area(&i), 2500.0));
+        let u = union(&a, &b);
+        assert_eq!(u.len(), 1);
+        assert!(approx_eq(total_area(&u), 17500.0));
+        let d = difference(&a, &b);
+        assert_eq!(d.len(), 1);
+        assert!(approx_eq(total_area(&d), 7500.0));
+        assert!(approx_eq(total_area(&xor(&a, &b)), 15000.0));
+    }
+
+    #[test]
+    fn test_multiple_pieces() {
+        // a bar across a U shape cuts it into two pieces.
+        let u = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(30.0, 0.0), Point::new(30.0, 70.0), Point::new(70.0, 70.0),
+                                  Point::new(70.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)]);
+        let bar = Polygon::new(vec![Point::new(-10.0, 10.0), Point::new(110.0, 10.0), Point::new(110.0, 20.0), Point::new(-10.0, 20.0)]);
+        let i = intersection(&u, &bar);
+        assert_eq!(i.len(), 2);
+        assert!(approx_eq(total_area(&i), 600.0));
+    }
+
+    #[test]
+    fn test_nested_and_disjoint() {
+        let outer = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        let inner = Polygon::from_rect(&Rect::new(25.0, 25.0, 50.0, 50.0));
+        assert_eq!(intersection(&outer, &inner), vec![inner.clone()]);
+        assert_eq!(union(&outer, &inner), vec![outer.clone()]);
+        let d = difference(&outer, &inner);
+        assert_eq!(d.len(), 2);
+        assert!(approx_eq(d[0].signed_area() + d[1].signed_area(), 7500.0));
+        assert!(difference(&inner, &outer).is_empty());
+
+        let far = Polygon::from_rect(&Rect::new(200.0, 0.0, 10.0, 10.0));
+        assert!(intersection(&outer, &far).is_empty());
+        assert_eq!(union(&outer, &far).len(), 2);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        // overlapping edges and shared corners come out exact, with no stray points.
+        let a = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        let b = Polygon::from_rect(&Rect::new(50.0, 0.0, 100.0, 100.0));
+        assert_eq!(intersection(&a, &b), vec![Polygon::from_rect(&Rect { x: 50.0, y: 0.0, w: 50.0, h: 100.0 })]);
+        let u = union(&a, &b);
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].len(), 4);
+        assert_eq!(u[0].area(), 15000.0);
+        assert_eq!(total_area(&difference(&a, &b)), 5000.0);
+
+        // squares side by side merge into one rectangle.
+        let left = Polygon::from_rect(&Rect::new(0.0, 0.0, 5.0, 5.0));
+        let right = Polygon::from_rect(&Rect::new(5.0, 0.0, 5.0, 5.0));
+        let u = union(&left, &right);
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].len(), 4);
+        assert_eq!(u[0].area(), 50.0);
+        assert!(u[0].points.iter().all(|p| (p.x == 0.0 || p.x == 10.0) && (p.y == 0.0 || p.y == 5.0)));
+        assert!(intersection(&left, &right).is_empty());
+        assert_eq!(difference(&left, &right), vec![left.clone()]);
+
+        // squares meeting at a corner stay apart, and a square against itself is all or nothing.
+        assert_eq!(union(&left, &Polygon::from_rect(&Rect::new(5.0, 5.0, 5.0, 5.0))).len(), 2);
+        assert_eq!(total_area(&intersection(&left, &left)), 25.0);
+        assert!(difference(&left, &left).is_empty());
+    }
+}
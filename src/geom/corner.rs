@@ -0,0 +1,168 @@
+use super::point::Point;
+use super::dist;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CornerStyle {
+    Round,
+    Chamfer,
+    Inverted,
+}
+
+// a fixed arc radius, or a fraction of the shorter adjoining edge.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CornerSize {
+    Radius(f64),
+    Fraction(f64),
+}
+
+// negative arcs run with decreasing angle, as drawn by cairo's arc_negative.
+#[derive(PartialEq, Debug)]
+pub struct Corner {
+    pub point: Point,
+    pub start: Point,
+    pub end: Point,
+    pub center: Point,
+    pub radius: f64,
+    pub start_angle: f64,
+    pub end_angle: f64,
+    pub negative: bool,
+}
+
+impl Corner {
+    // distance from the corner point back to where the cut begins on each edge.
+    pub fn cut(&self) -> f64 {
+        dist(&self.point, &self.start)
+    }
+
+    // the notch for CornerStyle::Inverted, bulging into the shape around the corner point.
+    pub fn inverted(&self) -> Corner {
+        Corner {
+            point: self.point,
+            start: self.start,
+            end: self.end,
+            center: self.point,
+            radius: self.cut(),
+            start_angle: self.point.angle_to(&self.start),
+            end_angle: self.point.angle_to(&self.end),
+            negative: !self.negative,
+        }
+    }
+}
+
+// the cut is limited to half of either edge so neighbouring corners never overlap.
+pub fn corner(p0: &Point, p1: &Point, p2: &Point, size: CornerSize) -> Option<Corner> {
+    let len0 = dist(p1, p0);
+    let len1 = dist(p1, p2);
+    if len0 == 0.0 || len1 == 0.0 {
+        return None;
+    }
+    let u = (*p0 - *p1) / len0;
+    let v = (*p2 - *p1) / len1;
+    let cross = u.cross(&v);
+    let half = u.dot(&v).max(-1.0).min(1.0).acos() / 2.0;
+    if cross.abs() < 1e-9 || half <= 0.0 {
+        return None;
+    }
+
+    let max_cut = len0.min(len1) / 2.0;
+    let cut = match size {
+        CornerSize::Radius(r) => r / half.tan(),
+        CornerSize::Fraction(f) => f * len0.min(len1),
+    };
+    let cut = cut.max(0.0).min(max_cut);
+    if cut == 0.0 {
+        return None;
+    }
+    let radius = cut * half.tan();
+
+    let start = *p1 + u * cut;
+    let end = *p1 + v * cut;
+    let center = *p1 + (u + v).normalize() * (radius / half.sin());
+    Some(Corner {
+        start_angle: center.angle_to(&start),
+        end_angle: center.angle_to(&end),
+        point: *p1,
+        start: start,
+        end: end,
+        center: center,
+        radius: radius,
+        negative: cross > 0.0,
+    })
+}
+
+pub fn corners(points: &[Point], size: CornerSize) -> Vec<Option<Corner>> {
+    let n = points.len();
+    (0..n)
+        .map(|i| corner(&points[(i + n - 1) % n], &points[i], &points[(i + 1) % n], size))
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use math::{ PI, HALF_PI };
+
+    #[test]
+    fn test_square_corner() {
+        let c = corner(&Point::new(0.0, 0.0), &Point::new(100.0, 0.0), &Point::new(100.0, 100.0),
+                       CornerSize::Radius(10.0)).expect("no corner");
+        assert!(approx_eq(c.radius, 10.0));
+        assert!(approx_eq(c.cut(), 10.0));
+        assert!(approx_eq(c.start.x, 90.0) && approx_eq(c.start.y, 0.0));
+        assert!(approx_eq(c.end.x, 100.0) && approx_eq(c.end.y, 10.0));
+        assert!(approx_eq(c.center.x, 90.0) && approx_eq(c.center.y, 10.0));
+        assert!(approx_eq(c.start_angle, -HALF_PI));
+        assert!(approx_eq(c.end_angle, 0.0));
+        assert!(!c.negative);
+    }
+
+    #[test]
+    fn test_reverse_corner() {
+        let c = corner(&Point::new(100.0, 100.0), &Point::new(100.0, 0.0), &Point::new(0.0, 0.0),
+                       CornerSize::Radius(10.0)).expect("no corner");
+        assert!(approx_eq(c.start_angle, 0.0));
+        assert!(approx_eq(c.end_angle, -HALF_PI));
+        assert!(c.negative);
+    }
+
+    #[test]
+    fn test_inverted() {
+        let c = corner(&Point::new(0.0, 0.0), &Point::new(100.0, 0.0), &Point::new(100.0, 100.0),
+                       CornerSize::Radius(10.0)).expect("no corner").inverted();
+        assert_eq!(c.center, Point::new(100.0, 0.0));
+        assert!(approx_eq(c.radius, 10.0));
+        assert!(approx_eq(c.start_angle, PI) && approx_eq(c.end_angle, HALF_PI));
+        assert!(c.negative);
+        // running back from PI to HALF_PI the arc passes through the square, not outside it.
+        let mid = c.center + Point::from_polar(PI * 0.75, c.radius);
+        assert!(approx_eq(mid.x, 100.0 - 50.0_f64.sqrt()) && approx_eq(mid.y, 50.0_f64.sqrt()));
+    }
+
+    #[test]
+    fn test_tangent_to_edges() {
+        // an acute corner: the center must be `radius` from both edges.
+        let c = corner(&Point::new(0.0, 0.0), &Point::new(100.0, 0.0), &Point::new(20.0, 40.0),
+                       CornerSize::Radius(5.0)).expect("no corner");
+        assert!(approx_eq(c.center.y, 5.0));
+        assert!(approx_eq(dist(&c.center, &c.start), 5.0));
+        assert!(approx_eq(dist(&c.center, &c.end), 5.0));
+        let edge = Point::new(20.0, 40.0) - Point::new(100.0, 0.0);
+        assert!(approx_eq((c.end - c.center).dot(&edge), 0.0));
+    }
+
+    #[test]
+    fn test_limits() {
+        // the cut is clamped to half the shorter edge.
+        let c = corner(&Point::new(0.0, 0.0), &Point::new(10.0, 0.0), &Point::new(10.0, 100.0),
+                       CornerSize::Radius(50.0)).expect("no corner");
+        assert!(approx_eq(c.cut(), 5.0));
+        let c = corner(&Point::new(0.0, 0.0), &Point::new(10.0, 0.0), &Point::new(10.0, 100.0),
+                       CornerSize::Fraction(0.25)).expect("no corner");
+        assert!(approx_eq(c.cut(), 2.5));
+        // straight and degenerate corners are left alone.
+        assert_eq!(corner(&Point::new(0.0, 0.0), &Point::new(10.0, 0.0), &Point::new(20.0, 0.0), CornerSize::Radius(5.0)), None);
+        assert_eq!(corner(&Point::new(0.0, 0.0), &Point::new(0.0, 0.0), &Point::new(20.0, 0.0), CornerSize::Radius(5.0)), None);
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{ HashMap, HashSet };
+use super::point::Point;
+use super::triangle::Triangle;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Delaunay {
+    pub points: Vec<Point>,
+    // clockwise on screen.
+    pub triangles: Vec<[usize; 3]>,
+    // the triangle across the edge from corner j to corner j + 1.
+    pub adjacent: Vec<[Option<usize>; 3]>,
+    pub edges: Vec<Edge>,
+}
+
+// a < b, with the one or two triangles either side.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Edge {
+    pub a: usize,
+    pub b: usize,
+    pub triangles: Vec<usize>,
+}
+
+impl Edge {
+    pub fn is_boundary(&self) -> bool {
+        self.triangles.len() == 1
+    }
+}
+
+impl Delaunay {
+    // bowyer-watson. repeated points are left out, and points all on one line give no triangles.
+    pub fn new(points: &[Point]) -> Delaunay {
+        let triangles = bowyer_watson(points);
+        let mut edge_map: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, t) in triangles.iter().enumerate() {
+            for j in 0..3 {
+                let (a, b) = (t[j], t[(j + 1) % 3]);
+                edge_map.entry((a.min(b), a.max(b))).or_default().push(i);
+            }
+        }
+        let adjacent = triangles.iter().enumerate().map(|(i, t)| {
+            let mut across = [None; 3];
+            for j in 0..3 {
+                let (a, b) = (t[j], t[(j + 1) % 3]);
+                across[j] = edge_map[&(a.min(b), a.max(b))].iter().cloned().find(|&other| other != i);
+            }
+            across
+        }).collect();
+        let mut edges: Vec<Edge> = edge_map.into_iter()
+            .map(|((a, b), triangles)| Edge { a: a, b: b, triangles: triangles })
+            .collect();
+        edges.sort_by_key(|e| (e.a, e.b));
+        Delaunay {
+            points: points.to_vec(),
+            triangles: triangles,
+            adjacent: adjacent,
+            edges: edges,
+        }
+    }
+
+    pub fn triangle(&self, i: usize) -> Triangle {
+        let t = self.triangles[i];
+        Triangle::new(self.points[t[0]], self.points[t[1]], self.points[t[2]])
+    }
+
+    pub fn to_triangles(&self) -> Vec<Triangle> {
+        (0..self.triangles.len()).map(|i| self.triangle(i)).collect()
+    }
+
+    pub fn neighbors(&self, i: usize) -> Vec<usize> {
+        self.edges.iter()
+            .filter_map(|e| if e.a == i { Some(e.b) } else if e.b == i { Some(e.a) } else { None })
+            .collect()
+    }
+
+    pub fn triangles_around(&self, i: usize) -> Vec<usize> {
+        (0..self.triangles.len()).filter(|&t| self.triangles[t].contains(&i)).collect()
+    }
+
+    pub fn edge(&self, a: usize, b: usize) -> Option<&Edge> {
+        let key = (a.min(b), a.max(b));
+        self.edges.binary_search_by_key(&key, |e| (e.a, e.b)).ok().map(|i| &self.edges[i])
+    }
+}
+
+fn bowyer_watson(points: &[Point]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    // the first triangle is the first three distinct points that aren't in a line.
+    let mut seen = HashSet::new();
+    let unique: Vec<usize> = (0..n).filter(|&i| seen.insert((points[i].x.to_bits(), points[i].y.to_bits()))).collect();
+    let third = unique.iter().skip(2).cloned()
+        .find(|&k| (points[unique[1]] - points[unique[0]]).cross(&(points[k] - points[unique[0]])) != 0.0);
+    let (a, b, c) = match third {
+        Some(k) => (unique[0], unique[1], k),
+        None => return Vec::new(),
+    };
+    let (b, c) = if (points[b] - points[a]).cross(&(points[c] - points[a])) > 0.0 { (b, c) } else { (c, b) };
+    // index n stands for a point infinitely far away, so each hull edge has a ghost triangle
+    // outside it. a fixed super triangle, however big, loses triangles along a nearly flat hull.
+    let mut triangles = vec![[a, b, c], [b, a, n], [c, b, n], [a, c, n]];
+
+    for &i in unique.iter().filter(|&&i| i != a && i != b && i != c) {
+        let p = &points[i];
+        // the triangles this point spoils leave a hole, which is filled with a fan from the point.
+        let (bad, good): (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles.into_iter()
+            .partition(|t| spoils(points, t, p));
+        triangles = good;
+        let edges: HashSet<(usize, usize)> = bad.iter()
+            .flat_map(|t| vec![(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+            .collect();
+        for &(a, b) in edges.iter() {
+            // shared edges appear once each way round.
+            if !edges.contains(&(b, a)) {
+                triangles.push(if a == n { [b, i, n] } else if b == n { [i, a, n] } else { [a, b, i] });
+            }
+        }
+    }
+    triangles.retain(|t| t.iter().all(|&v| v < n));
+    // start each at its lowest corner so the order is predictable.
+    for t in triangles.iter_mut() {
+        while t[0] > t[1] || t[0] > t[2] {
+            t.rotate_left(1);
+        }
+    }
+    triangles.sort();
+    triangles
+}
+
+// true if `p` is inside the circumcircle of `t`. a ghost triangle, with the far point last,
+// has the open half plane outside its hull edge for a circumcircle, along with the edge itself.
+fn spoils(points: &[Point], t: &[usize; 3], p: &Point) -> bool {
+    if t[2] < points.len() {
+        return in_circumcircle(&points[t[0]], &points[t[1]], &points[t[2]], p);
+    }
+    let (a, b) = (points[t[0]], points[t[1]]);
+    let side = (b - a).cross(&(*p - a));
+    side > 0.0 || (side == 0.0 && (a - *p).dot(&(b - *p)) < 0.0)
+}
+
+// strictly inside, whichever way a, b and c run.
+pub fn in_circumcircle(a: &Point, b: &Point, c: &Point, p: &Point) -> bool {
+    let (ad, bd, cd) = (*a - *p, *b - *p, *c - *p);
+    let det = ad.dot(&ad) * bd.cross(&cd) - bd.dot(&bd) * ad.cross(&cd) + cd.dot(&cd) * ad.cross(&bd);
+    let orientation = (*b - *a).cross(&(*c - *a));
+    det * orientation.signum() > 0.0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Random;
+    use geom::hull::convex_hull;
+
+    #[test]
+    fn test_square() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0),
+                          Point::new(0.0, 10.0), Point::new(5.0, 5.0)];
+        let d = Delaunay::new(&points);
+        assert_eq!(d.triangles.len(), 4);
+        assert_eq!(d.edges.len(), 8);
+        assert_eq!(d.neighbors(4), vec![0, 1, 2, 3]);
+        assert_eq!(d.neighbors(0), vec![1, 3, 4]);
+        assert_eq!(d.triangles_around(4).len(), 4);
+        assert!(d.edge(1, 0).expect("no edge").is_boundary());
+        assert!(!d.edge(2, 4).expect("no edge").is_boundary());
+        assert!(d.edge(0, 2).is_none());
+        for t in d.to_triangles().iter() {
+            assert!(t.signed_area() > 0.0);
+            assert_eq!(t.area(), 25.0);
+        }
+    }
+
+    #[test]
+    fn test_empty_circumcircles() {
+        let mut rand = Random::from_seed(5);
+        let points: Vec<Point> = (0..150).map(|_| Point::new(rand.float(0.0, 200.0), rand.float(0.0, 100.0))).collect();
+        let d = Delaunay::new(&points);
+        for t in d.to_triangles().iter() {
+            let circle = t.circumcircle().expect("flat triangle");
+            for p in points.iter() {
+                assert!(p.dist(&circle.center()) >= circle.r - 1e-6);
+            }
+        }
+        // the triangles exactly cover the convex hull.
+        let area = d.to_triangles().iter().fold(0.0, |sum, t| sum + t.area());
+        let hull = convex_hull(&points);
+        assert!((area - hull.area()).abs() < 1e-6);
+        assert_eq!(d.triangles.len(), 2 * points.len() - 2 - hull.len());
+        assert_eq!(d.edges.iter().filter(|e| e.is_boundary()).count(), hull.len());
+    }
+
+    #[test]
+    fn test_flat_hull() {
+        // a very shallow arc with one point off to the side: the hull is nearly a line.
+        let mut points: Vec<Point> = (0..60).map(|i| Point::new(i as f64, -1e-5 * (i as f64 - 30.0).powi(2))).collect();
+        points.push(Point::new(30.0, 1.0));
+        let d = Delaunay::new(&points);
+        let hull = convex_hull(&points);
+        assert_eq!(d.triangles.len(), 2 * points.len() - 2 - hull.len());
+        let area = d.to_triangles().iter().fold(0.0, |sum, t| sum + t.area());
+        assert!((area - hull.area()).abs() < 1e-6);
+        assert_eq!(d.edges.iter().filter(|e| e.is_boundary()).count(), hull.len());
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let mut rand = Random::from_seed(6);
+        let points: Vec<Point> = (0..60).map(|_| Point::new(rand.float(0.0, 50.0), rand.float(0.0, 50.0))).collect();
+        let d = Delaunay::new(&points);
+        for (i, across) in d.adjacent.iter().enumerate() {
+            for j in 0..3 {
+                let (a, b) = (d.triangles[i][j], d.triangles[i][(j + 1) % 3]);
+                match across[j] {
+                    Some(other) => {
+                        assert!(d.triangles[other].contains(&a) && d.triangles[other].contains(&b));
+                        assert!(d.adjacent[other].contains(&Some(i)));
+                    }
+                    None => assert!(d.edge(a, b).expect("no edge").is_boundary()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert!(Delaunay::new(&[Point::new(0.0, 0.0), Point::new(1.0, 0.0)]).triangles.is_empty());
+        let line: Vec<Point> = (0..5).map(|i| Point::new(i as f64, i as f64)).collect();
+        assert!(Delaunay::new(&line).triangles.is_empty());
+        let repeated = vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 4.0), Point::new(4.0, 0.0)];
+        let d = Delaunay::new(&repeated);
+        assert_eq!(d.triangles, vec![[0, 1, 2]]);
+    }
+}
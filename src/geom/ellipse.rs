@@ -0,0 +1,85 @@
+use super::point::Point;
+use super::rect::Rect;
+use math::PI;
+
+// rotation turns the way cairo's rotate does.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ellipse {
+    pub x: f64,
+    pub y: f64,
+    pub rx: f64,
+    pub ry: f64,
+    pub rotation: f64,
+}
+
+impl Ellipse {
+    pub fn new(x: f64, y: f64, rx: f64, ry: f64, rotation: f64) -> Ellipse {
+        Ellipse { x: x, y: y, rx: rx, ry: ry, rotation: rotation }
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub fn area(&self) -> f64 {
+        PI * self.rx * self.ry
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        let (a, b) = (self.rx.abs(), self.ry.abs());
+        PI * (3.0 * (a + b) - ((3.0 * a + b) * (a + 3.0 * b)).sqrt())
+    }
+
+    pub fn bounds(&self) -> Rect {
+        let (sin, cos) = self.rotation.sin_cos();
+        let w = ((self.rx * cos).powi(2) + (self.ry * sin).powi(2)).sqrt();
+        let h = ((self.rx * sin).powi(2) + (self.ry * cos).powi(2)).sqrt();
+        Rect::new(self.x - w, self.y - h, w * 2.0, h * 2.0)
+    }
+
+    pub fn point_at_angle(&self, angle: f64) -> Point {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (px, py) = (self.rx * angle.cos(), self.ry * angle.sin());
+        Point::new(self.x + px * cos - py * sin, self.y + px * sin + py * cos)
+    }
+
+    pub fn contains_point(&self, p: &Point) -> bool {
+        if self.rx == 0.0 || self.ry == 0.0 {
+            return false;
+        }
+        // into the ellipse's own unrotated space.
+        let (sin, cos) = self.rotation.sin_cos();
+        let d = *p - self.center();
+        let (lx, ly) = (d.x * cos + d.y * sin, d.y * cos - d.x * sin);
+        (lx / self.rx).powi(2) + (ly / self.ry).powi(2) <= 1.0
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    #[test]
+    fn test_measurements() {
+        let e = Ellipse::new(10.0, 20.0, 8.0, 2.0, 0.0);
+        assert!(approx_eq(e.area(), PI * 16.0));
+        assert_eq!(e.bounds(), Rect::new(2.0, 18.0, 16.0, 4.0));
+        assert!(e.contains_point(&Point::new(17.0, 20.0)));
+        assert!(!e.contains_point(&Point::new(10.0, 23.0)));
+        // a circle's perimeter comes out exact.
+        assert!(approx_eq(Ellipse::new(0.0, 0.0, 5.0, 5.0, 0.0).perimeter(), PI * 10.0));
+    }
+
+    #[test]
+    fn test_rotated() {
+        let e = Ellipse::new(0.0, 0.0, 8.0, 2.0, PI / 2.0);
+        let bounds = e.bounds();
+        assert!(approx_eq(bounds.w, 4.0) && approx_eq(bounds.h, 16.0));
+        assert!(e.contains_point(&Point::new(0.0, 7.0)));
+        assert!(!e.contains_point(&Point::new(7.0, 0.0)));
+        let p = e.point_at_angle(0.0);
+        assert!(approx_eq(p.x, 0.0) && approx_eq(p.y, 8.0));
+    }
+}
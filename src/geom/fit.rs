@@ -0,0 +1,198 @@
+use super::point::Point;
+use super::bezier::CubicBezier;
+
+// schneider's least squares fit. no point is left further than tolerance from the curves.
+pub fn fit_curve(points: &[Point], tolerance: f64) -> Vec<CubicBezier> {
+    let mut clean: Vec<Point> = Vec::new();
+    for p in points.iter() {
+        if clean.last().map_or(true, |last| last != p) {
+            clean.push(*p);
+        }
+    }
+    let mut result = Vec::new();
+    let n = clean.len();
+    if n < 2 {
+        return result;
+    }
+    let start_tangent = (clean[1] - clean[0]).normalize();
+    let end_tangent = (clean[n - 2] - clean[n - 1]).normalize();
+    fit_cubic(&clean, start_tangent, end_tangent, tolerance.max(1e-9), &mut result);
+    result
+}
+
+// fits one curve to `points`, splitting at the worst point and recursing if it's not close enough.
+// the tangents are unit vectors pointing into the curve at each end.
+fn fit_cubic(points: &[Point], start_tangent: Point, end_tangent: Point, tolerance: f64, result: &mut Vec<CubicBezier>) {
+    let n = points.len();
+    if n == 2 {
+        let d = points[0].dist(&points[1]) / 3.0;
+        result.push(CubicBezier::new(points[0], points[0] + start_tangent * d, points[1] + end_tangent * d, points[1]));
+        return;
+    }
+
+    let mut u = chord_lengths(points);
+    let mut curve = generate_bezier(points, &u, start_tangent, end_tangent);
+    let (mut error, mut split) = max_error(points, &curve, &u);
+    if error <= tolerance {
+        result.push(curve);
+        return;
+    }
+    // when it's nearly there, improving the parameters is cheaper than splitting.
+    if error <= tolerance * 4.0 {
+        for _ in 0..20 {
+            u = reparameterize(points, &curve, &u);
+            curve = generate_bezier(points, &u, start_tangent, end_tangent);
+            let (e, s) = max_error(points, &curve, &u);
+            error = e;
+            split = s;
+            if error <= tolerance {
+                result.push(curve);
+                return;
+            }
+        }
+    }
+
+    let mut center = points[split - 1] - points[split + 1];
+    if center.magnitude() == 0.0 {
+        center = (points[split - 1] - points[split]).perpendicular();
+    }
+    let center = center.normalize();
+    fit_cubic(&points[..split + 1], start_tangent, center, tolerance, result);
+    fit_cubic(&points[split..], -center, end_tangent, tolerance, result);
+}
+
+// the least squares curve for `points` at parameters `u` with the end tangents fixed,
+// solving only for how far along the tangents the control points sit.
+fn generate_bezier(points: &[Point], u: &[f64], start_tangent: Point, end_tangent: Point) -> CubicBezier {
+    let first = points[0];
+    let last = points[points.len() - 1];
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (p, &t) in points.iter().zip(u.iter()) {
+        let mt = 1.0 - t;
+        let b0 = mt * mt * mt;
+        let b1 = 3.0 * mt * mt * t;
+        let b2 = 3.0 * mt * t * t;
+        let b3 = t * t * t;
+        let a0 = start_tangent * b1;
+        let a1 = end_tangent * b2;
+        c00 += a0.dot(&a0);
+        c01 += a0.dot(&a1);
+        c11 += a1.dot(&a1);
+        let tmp = *p - (first * (b0 + b1) + last * (b2 + b3));
+        x0 += a0.dot(&tmp);
+        x1 += a1.dot(&tmp);
+    }
+
+    let det = c00 * c11 - c01 * c01;
+    let (alpha0, alpha1) = if det.abs() > 1e-12 {
+        ((x0 * c11 - x1 * c01) / det, (c00 * x1 - c01 * x0) / det)
+    }
+    else {
+        (0.0, 0.0)
+    };
+    // fall back to a third of the chord when the solution is degenerate or points backwards.
+    let chord = first.dist(&last);
+    let epsilon = 1e-6 * chord;
+    if alpha0 < epsilon || alpha1 < epsilon {
+        let d = chord / 3.0;
+        return CubicBezier::new(first, first + start_tangent * d, last + end_tangent * d, last);
+    }
+    CubicBezier::new(first, first + start_tangent * alpha0, last + end_tangent * alpha1, last)
+}
+
+// one newton step per point towards the parameter of its closest point on the curve.
+fn reparameterize(points: &[Point], curve: &CubicBezier, u: &[f64]) -> Vec<f64> {
+    points.iter()
+        .zip(u.iter())
+        .map(|(p, &t)| {
+            let offset = curve.point_at(t) - *p;
+            let d1 = curve.derivative(t);
+            let d2 = curve.second_derivative(t);
+            let denominator = d1.dot(&d1) + offset.dot(&d2);
+            if denominator.abs() < 1e-12 {
+                t
+            }
+            else {
+                (t - offset.dot(&d1) / denominator).max(0.0).min(1.0)
+            }
+        })
+        .collect()
+}
+
+// the furthest any point lies from the curve, and its index. never the end points,
+// so the index is always somewhere the points can be split.
+fn max_error(points: &[Point], curve: &CubicBezier, u: &[f64]) -> (f64, usize) {
+    let n = points.len();
+    let mut error = 0.0;
+    let mut split = n / 2;
+    for i in 1..n - 1 {
+        let d = curve.point_at(u[i]).dist(&points[i]);
+        if d > error {
+            error = d;
+            split = i;
+        }
+    }
+    (error, split)
+}
+
+fn chord_lengths(points: &[Point]) -> Vec<f64> {
+    let mut u = vec![0.0];
+    for i in 1..points.len() {
+        let previous = u[i - 1];
+        u.push(previous + points[i].dist(&points[i - 1]));
+    }
+    let total = u[u.len() - 1];
+    u.iter().map(|d| d / total).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distance_to_chain(p: &Point, curves: &[CubicBezier]) -> f64 {
+        curves.iter().map(|c| c.closest_point(p).dist(p)).fold(f64::MAX, f64::min)
+    }
+
+    #[test]
+    fn test_single_curve() {
+        let original = CubicBezier::new(Point::new(0.0, 0.0), Point::new(20.0, 80.0), Point::new(80.0, 80.0), Point::new(100.0, 0.0));
+        let points: Vec<Point> = (0..30).map(|i| original.point_at(i as f64 / 29.0)).collect();
+        // close enough to start with that refining the parameters finds the curve again.
+        let curves = fit_curve(&points, 2.0);
+        assert_eq!(curves.len(), 1);
+        for p in points.iter() {
+            assert!(distance_to_chain(p, &curves) <= 2.0);
+        }
+        assert_eq!(curves[0].p0, points[0]);
+        assert_eq!(curves[0].p3, points[29]);
+
+        let line = fit_curve(&[Point::new(0.0, 0.0), Point::new(50.0, 0.0), Point::new(100.0, 0.0)], 0.1);
+        assert_eq!(line.len(), 1);
+        assert!(fit_curve(&[Point::new(1.0, 1.0), Point::new(1.0, 1.0)], 0.1).is_empty());
+    }
+
+    #[test]
+    fn test_within_tolerance() {
+        // a few periods of a sine wave needs several curves.
+        let points: Vec<Point> = (0..200).map(|i| {
+            let x = i as f64 * 2.0;
+            Point::new(x, (x / 20.0).sin() * 40.0)
+        }).collect();
+        for &tolerance in [0.5, 2.0].iter() {
+            let curves = fit_curve(&points, tolerance);
+            assert!(curves.len() > 1 && curves.len() < 40);
+            for pair in curves.windows(2) {
+                assert_eq!(pair[0].p3, pair[1].p0);
+                // neighbours share a tangent where they meet.
+                let t0 = pair[0].tangent(1.0);
+                let t1 = pair[1].tangent(0.0);
+                assert!(t0.dot(&t1) > 0.999);
+            }
+            for p in points.iter() {
+                assert!(distance_to_chain(p, &curves) <= tolerance);
+            }
+        }
+        assert!(fit_curve(&points, 0.5).len() > fit_curve(&points, 2.0).len());
+    }
+}
@@ -0,0 +1,271 @@
+use super::point::Point;
+use super::circle::Circle;
+use super::polygon::Polygon;
+use super::line::{ Intersection, Linear, Segment };
+use super::dist_to_segment;
+use random::Random;
+
+// andrew's monotone chain. clockwise on screen, without non-finite points or points along edges.
+pub fn convex_hull(points: &[Point]) -> Polygon {
+    let mut sorted: Vec<Point> = points.iter().filter(|p| p.x.is_finite() && p.y.is_finite()).cloned().collect();
+    sorted.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap().then(a.y.partial_cmp(&b.y).unwrap()));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return Polygon::new(sorted);
+    }
+    // lower and upper chains, each keeping only right hand turns.
+    let mut hull: Vec<Point> = Vec::new();
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &Point>> = if pass == 0 { Box::new(sorted.iter()) } else { Box::new(sorted.iter().rev()) };
+        for p in iter {
+            while hull.len() >= start + 2 && (hull[hull.len() - 1] - hull[hull.len() - 2]).cross(&(*p - hull[hull.len() - 1])) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        // the last point of each chain starts the other.
+        hull.pop();
+    }
+    Polygon::new(hull).clockwise()
+}
+
+// welzl's algorithm on the convex hull.
+pub fn enclosing_circle(points: &[Point]) -> Circle {
+    let mut hull = convex_hull(points).points;
+    if hull.is_empty() {
+        return Circle::new(0.0, 0.0, 0.0);
+    }
+    // taken in a random order the loops below run in expected linear time, where points in
+    // order round the hull are quadratic or worse. a fixed seed keeps the result repeatable.
+    Random::from_seed(1).shuffle(&mut hull);
+    let mut circle = Circle::new(hull[0].x, hull[0].y, 0.0);
+    for i in 1..hull.len() {
+        if contains(&circle, &hull[i]) {
+            continue;
+        }
+        // hull[i] must lie on the boundary of the circle around the first i + 1 points.
+        circle = circle_from_two(&hull[i], &hull[0]);
+        for j in 1..i {
+            if contains(&circle, &hull[j]) {
+                continue;
+            }
+            circle = circle_from_two(&hull[i], &hull[j]);
+            for k in 0..j {
+                if !contains(&circle, &hull[k]) {
+                    circle = Circle::through_points(&hull[i], &hull[j], &hull[k])
+                        .unwrap_or_else(|| circle_from_two(&hull[i], &hull[k]));
+                }
+            }
+        }
+    }
+    circle
+}
+
+fn contains(circle: &Circle, p: &Point) -> bool {
+    p.dist(&circle.center()) <= circle.r * (1.0 + 1e-12) + 1e-12
+}
+
+fn circle_from_two(p0: &Point, p1: &Point) -> Circle {
+    let center = p0.lerp(p1, 0.5);
+    Circle::new(center.x, center.y, p0.dist(p1) / 2.0)
+}
+
+// rotating calipers. usually not axis aligned, so it comes back as a clockwise polygon.
+pub fn oriented_bounds(points: &[Point]) -> Polygon {
+    let hull = convex_hull(points).points;
+    let n = hull.len();
+    if n < 3 {
+        return Polygon::new(hull);
+    }
+    let mut best_area = f64::INFINITY;
+    let mut best = hull.clone();
+    // the hull points furthest along, furthest across and furthest back from each edge.
+    // as the edges turn, each only ever moves forward round the hull.
+    let (mut far, mut high, mut back) = (0, 0, 0);
+    for i in 0..n {
+        let base = hull[i];
+        let u = (hull[(i + 1) % n] - base).normalize();
+        // clockwise on screen, the hull lies to the right of each edge, which is +perpendicular.
+        let v = u.perpendicular();
+        let along = |k: usize| (hull[k % n] - base).dot(&u);
+        let across = |k: usize| (hull[k % n] - base).dot(&v);
+        if i == 0 {
+            far = (0..n).max_by(|&a, &b| along(a).partial_cmp(&along(b)).unwrap()).unwrap();
+            high = (0..n).max_by(|&a, &b| across(a).partial_cmp(&across(b)).unwrap()).unwrap();
+            back = (0..n).min_by(|&a, &b| along(a).partial_cmp(&along(b)).unwrap()).unwrap();
+        }
+        for _ in 0..n {
+            if along(far + 1) < along(far) { break; }
+            far = (far + 1) % n;
+        }
+        for _ in 0..n {
+            if across(high + 1) < across(high) { break; }
+            high = (high + 1) % n;
+        }
+        for _ in 0..n {
+            if along(back + 1) > along(back) { break; }
+            back = (back + 1) % n;
+        }
+        let (min_u, max_u, max_v) = (along(back), along(far), across(high));
+        let area = (max_u - min_u) * max_v;
+        if area < best_area {
+            best_area = area;
+            best = vec![base + u * min_u, base + u * max_u, base + u * max_u + v * max_v, base + u * min_u + v * max_v];
+        }
+    }
+    Polygon::new(best).clockwise()
+}
+
+// digs into the convex hull while an edge is over concavity times the distance to the nearest point inside it. around 2 works well.
+pub fn concave_hull(points: &[Point], concavity: f64) -> Polygon {
+    let mut hull = convex_hull(points).points;
+    if hull.len() < 3 {
+        return Polygon::new(hull);
+    }
+    let mut inner: Vec<Point> = points.iter()
+        .filter(|p| p.x.is_finite() && p.y.is_finite() && !hull.contains(p))
+        .cloned()
+        .collect();
+    inner.dedup();
+    let mut i = 0;
+    while i < hull.len() {
+        match dig(&hull, &inner, i, concavity) {
+            Some(k) => {
+                hull.insert(i + 1, inner.remove(k));
+            }
+            None => i += 1,
+        }
+    }
+    Polygon::new(hull)
+}
+
+// the inner point to dig edge i in to, if any.
+fn dig(hull: &[Point], inner: &[Point], i: usize, concavity: f64) -> Option<usize> {
+    let n = hull.len();
+    let a = hull[i];
+    let b = hull[(i + 1) % n];
+    let length = a.dist(&b);
+    let edge_dist = |p: &Point, k: usize| dist_to_segment(p, &hull[k], &hull[(k + 1) % n]);
+
+    // the nearest point that is nearer this edge than any other.
+    let (k, p) = inner.iter()
+        .enumerate()
+        .filter(|&(_, p)| (0..n).all(|e| e == i || edge_dist(p, e) >= edge_dist(p, i)))
+        .min_by(|&(_, p), &(_, q)| edge_dist(p, i).partial_cmp(&edge_dist(q, i)).unwrap())?;
+    let reach = a.dist(p).min(b.dist(p));
+    if reach == 0.0 || length / reach <= concavity {
+        return None;
+    }
+    // the new edges mustn't cross the hull or cut any other point off.
+    let triangle = Polygon::new(vec![a, *p, b]);
+    if inner.iter().any(|q| q != p && triangle.contains_point(q)) {
+        return None;
+    }
+    for &(e0, e1) in [(a, *p), (*p, b)].iter() {
+        let edge = Segment::new(e0, e1);
+        for e in 0..n {
+            let (h0, h1) = (hull[e], hull[(e + 1) % n]);
+            if h0 == a || h0 == b || h1 == a || h1 == b {
+                continue;
+            }
+            if edge.intersect(&Segment::new(h0, h1)) != Intersection::None {
+                return None;
+            }
+        }
+    }
+    Some(k)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use math::TWO_PI;
+
+    fn cloud(seed: usize) -> Vec<Point> {
+        let mut rand = Random::from_seed(seed);
+        (0..200).map(|_| Point::new(rand.float(0.0, 100.0), rand.float(0.0, 50.0))).collect()
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let mut points = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0),
+                              Point::new(5.0, 0.0), Point::new(5.0, 5.0), Point::new(2.0, 7.0), Point::new(10.0, 10.0)];
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 4);
+        assert!(hull.is_clockwise());
+        assert_eq!(hull.area(), 100.0);
+        points.reverse();
+        assert_eq!(convex_hull(&points).area(), 100.0);
+        points.push(Point::new(f64::NAN, 3.0));
+        assert_eq!(convex_hull(&points).len(), 4);
+
+        let hull = convex_hull(&cloud(1));
+        assert!(hull.is_convex());
+        for p in cloud(1).iter() {
+            assert!(hull.winding_number(p) != 0 || hull.edges().iter().any(|&(a, b)| dist_to_segment(p, &a, &b) < 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_enclosing_circle() {
+        let square = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0), Point::new(4.0, 6.0)];
+        let c = enclosing_circle(&square);
+        assert!(approx_eq(c.x, 5.0) && approx_eq(c.y, 5.0));
+        assert!(approx_eq(c.r, 50.0f64.sqrt()));
+
+        // an obtuse triangle is held by the circle on its longest side.
+        let c = enclosing_circle(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(5.0, 1.0)]);
+        assert!(approx_eq(c.x, 5.0) && approx_eq(c.y, 0.0) && approx_eq(c.r, 5.0));
+
+        let points = cloud(2);
+        let c = enclosing_circle(&points);
+        assert!(points.iter().all(|p| p.dist(&c.center()) <= c.r + 1e-9));
+        // at least two points sit on the circle.
+        assert!(points.iter().filter(|p| (p.dist(&c.center()) - c.r).abs() < 1e-6).count() >= 2);
+
+        // many points all on the hull, in order, are the slow case without shuffling.
+        let ellipse: Vec<Point> = (0..5000).map(|i| Point::from_polar(TWO_PI * i as f64 / 5000.0, 1.0).scale(80.0, 30.0)).collect();
+        let c = enclosing_circle(&ellipse);
+        assert!(approx_eq(c.r, 80.0) && approx_eq(c.x, 0.0) && approx_eq(c.y, 0.0));
+    }
+
+    #[test]
+    fn test_oriented_bounds() {
+        // a rotated 40 x 10 rectangle of points comes back as itself.
+        let angle = 0.5;
+        let points: Vec<Point> = (0..=8)
+            .flat_map(|i| (0..=2).map(move |j| Point::new(i as f64 * 5.0, j as f64 * 5.0).rotate(angle)))
+            .collect();
+        let rect = oriented_bounds(&points);
+        assert_eq!(rect.len(), 4);
+        assert!(approx_eq(rect.area(), 400.0));
+        assert!(rect.is_clockwise());
+        let axis_aligned = Polygon::from_slice(&points).bounds();
+        assert!(rect.area() < axis_aligned.area());
+
+        let points = cloud(3);
+        let rect = oriented_bounds(&points);
+        assert!(rect.area() <= 100.0 * 50.0 + 1e-6);
+        assert!(points.iter().all(|p| rect.winding_number(p) != 0 ||
+                                      rect.edges().iter().any(|&(a, b)| dist_to_segment(p, &a, &b) < 1e-6)));
+    }
+
+    #[test]
+    fn test_concave_hull() {
+        // a U of points: the concave hull digs into the gap.
+        let points: Vec<Point> = cloud(4).into_iter()
+            .filter(|p| !(p.x > 30.0 && p.x < 70.0 && p.y < 35.0))
+            .collect();
+        let convex = convex_hull(&points);
+        let concave = concave_hull(&points, 2.0);
+        assert!(concave.area() < convex.area() * 0.9);
+        assert!(concave.is_clockwise());
+        for p in points.iter() {
+            assert!(concave.winding_number(p) != 0 || concave.edges().iter().any(|&(a, b)| dist_to_segment(p, &a, &b) < 1e-9));
+        }
+        assert!(approx_eq(concave_hull(&points, 1e9).area(), convex.area()));
+    }
+}
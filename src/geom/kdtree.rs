@@ -0,0 +1,160 @@
+use super::point::Point;
+
+// results are indices into the points it was built from.
+pub struct KdTree {
+    points: Vec<Point>,
+    // point indices arranged so the middle of each range splits it, alternating x and y.
+    order: Vec<usize>,
+}
+
+impl KdTree {
+    pub fn new(points: &[Point]) -> KdTree {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut order, 0);
+        KdTree { points: points.to_vec(), order: order }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, i: usize) -> Point {
+        self.points[i]
+    }
+
+    pub fn nearest(&self, p: &Point) -> Option<usize> {
+        self.k_nearest(p, 1).into_iter().next()
+    }
+
+    // nearest first.
+    pub fn k_nearest(&self, p: &Point, k: usize) -> Vec<usize> {
+        let mut best: Vec<(f64, usize)> = Vec::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(p, k, 0, self.order.len(), 0, &mut best);
+        }
+        best.into_iter().map(|(_, i)| i).collect()
+    }
+
+    // in no particular order.
+    pub fn within(&self, p: &Point, radius: f64) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.search_within(p, radius, 0, self.order.len(), 0, &mut result);
+        result
+    }
+
+    fn search_nearest(&self, p: &Point, k: usize, lo: usize, hi: usize, depth: usize, best: &mut Vec<(f64, usize)>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let i = self.order[mid];
+        let d = self.points[i].dist(p);
+        if best.len() < k || d < best[best.len() - 1].0 {
+            let at = best.iter().position(|&(bd, _)| bd > d).unwrap_or(best.len());
+            best.insert(at, (d, i));
+            best.truncate(k);
+        }
+        let offset = axis_value(p, depth) - axis_value(&self.points[i], depth);
+        let (near, far) = if offset < 0.0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search_nearest(p, k, near.0, near.1, depth + 1, best);
+        // the far side can only help if the splitting line is nearer than the worst so far.
+        if best.len() < k || offset.abs() < best[best.len() - 1].0 {
+            self.search_nearest(p, k, far.0, far.1, depth + 1, best);
+        }
+    }
+
+    fn search_within(&self, p: &Point, radius: f64, lo: usize, hi: usize, depth: usize, result: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let i = self.order[mid];
+        if self.points[i].dist(p) <= radius {
+            result.push(i);
+        }
+        let offset = axis_value(p, depth) - axis_value(&self.points[i], depth);
+        // points before the middle are no further along the axis, those after no nearer.
+        if offset <= radius {
+            self.search_within(p, radius, lo, mid, depth + 1, result);
+        }
+        if offset >= -radius {
+            self.search_within(p, radius, mid + 1, hi, depth + 1, result);
+        }
+    }
+}
+
+fn axis_value(p: &Point, depth: usize) -> f64 {
+    if depth % 2 == 0 { p.x } else { p.y }
+}
+
+// sorts each range about its middle point, on x then y, down to single points.
+fn build(points: &[Point], order: &mut [usize], depth: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+    order.sort_by(|&a, &b| axis_value(&points[a], depth).partial_cmp(&axis_value(&points[b], depth)).unwrap());
+    let mid = order.len() / 2;
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Random;
+
+    fn random_points(seed: usize, count: usize) -> Vec<Point> {
+        let mut rand = Random::from_seed(seed);
+        (0..count).map(|_| Point::new(rand.float(0.0, 200.0), rand.float(0.0, 100.0))).collect()
+    }
+
+    fn by_dist(points: &[Point], p: &Point) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&a, &b| points[a].dist(p).partial_cmp(&points[b].dist(p)).unwrap());
+        order
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = random_points(1, 1000);
+        let tree = KdTree::new(&points);
+        let mut rand = Random::from_seed(9);
+        for _ in 0..50 {
+            let p = Point::new(rand.float(-20.0, 220.0), rand.float(-20.0, 120.0));
+            let expected = by_dist(&points, &p);
+            assert_eq!(tree.nearest(&p), Some(expected[0]));
+            assert_eq!(tree.k_nearest(&p, 6), expected[..6].to_vec());
+        }
+        assert_eq!(tree.nearest(&points[17]), Some(17));
+    }
+
+    #[test]
+    fn test_within() {
+        let points = random_points(2, 600);
+        let tree = KdTree::new(&points);
+        let p = Point::new(80.0, 40.0);
+        let mut found = tree.within(&p, 15.0);
+        found.sort();
+        let expected: Vec<usize> = (0..points.len()).filter(|&i| points[i].dist(&p) <= 15.0).collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_small() {
+        let tree = KdTree::new(&[]);
+        assert!(tree.is_empty());
+        assert_eq!(tree.nearest(&Point::new(0.0, 0.0)), None);
+        // repeated points are all found.
+        let points = vec![Point::new(1.0, 1.0), Point::new(1.0, 1.0), Point::new(5.0, 5.0)];
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.k_nearest(&Point::new(0.0, 0.0), 5).len(), 3);
+        assert_eq!(tree.within(&Point::new(1.0, 1.0), 0.0).len(), 2);
+    }
+}
@@ -0,0 +1,345 @@
+use super::point::Point;
+use super::circle::Circle;
+use super::rect::Rect;
+use super::polygon::Polygon;
+
+// parameters are compared against this directly, distances relative to the size of the inputs.
+pub const EPSILON: f64 = 1e-9;
+
+// t is 0 at p0 and 1 at p1, as for rays and segments.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Line {
+    pub p0: Point,
+    pub p1: Point,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub p0: Point,
+    pub p1: Point,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Segment {
+    pub p0: Point,
+    pub p1: Point,
+}
+
+// each hit carries t on the first and u on the second.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hit {
+    pub point: Point,
+    pub t: f64,
+    pub u: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Intersection {
+    None,
+    Point(Hit),
+    // collinear and sharing a stretch between the two hits, in order of t.
+    Overlap(Hit, Hit),
+    // collinear and sharing an unbounded stretch, as overlapping lines or rays do.
+    Coincident,
+}
+
+impl Line {
+    pub fn new(p0: Point, p1: Point) -> Line {
+        Line { p0: p0, p1: p1 }
+    }
+
+    pub fn from_angle(p: Point, angle: f64) -> Line {
+        Line::new(p, p + Point::from_polar(angle, 1.0))
+    }
+}
+
+impl Ray {
+    pub fn new(p0: Point, p1: Point) -> Ray {
+        Ray { p0: p0, p1: p1 }
+    }
+
+    pub fn from_angle(p: Point, angle: f64) -> Ray {
+        Ray::new(p, p + Point::from_polar(angle, 1.0))
+    }
+}
+
+impl Segment {
+    pub fn new(p0: Point, p1: Point) -> Segment {
+        Segment { p0: p0, p1: p1 }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.p0.dist(&self.p1)
+    }
+
+    pub fn midpoint(&self) -> Point {
+        self.p0.lerp(&self.p1, 0.5)
+    }
+}
+
+// lines, rays and segments differ only in the range of t they cover.
+pub trait Linear {
+    fn p0(&self) -> Point;
+    fn p1(&self) -> Point;
+    fn t_range(&self) -> (f64, f64);
+
+    // not normalized.
+    fn direction(&self) -> Point {
+        self.p1() - self.p0()
+    }
+
+    fn point_at(&self, t: f64) -> Point {
+        self.p0() + self.direction() * t
+    }
+
+    fn contains_t(&self, t: f64) -> bool {
+        let (min, max) = self.t_range();
+        t >= min - EPSILON && t <= max + EPSILON
+    }
+
+    fn closest_t(&self, p: &Point) -> f64 {
+        let d = self.direction();
+        let len_sq = d.dot(&d);
+        if len_sq == 0.0 {
+            return 0.0;
+        }
+        let (min, max) = self.t_range();
+        ((*p - self.p0()).dot(&d) / len_sq).max(min).min(max)
+    }
+
+    fn closest_point(&self, p: &Point) -> Point {
+        self.point_at(self.closest_t(p))
+    }
+
+    fn dist_to_point(&self, p: &Point) -> f64 {
+        self.closest_point(p).dist(p)
+    }
+
+    // collinear parallels give any shared stretch as an overlap. zero length inputs never meet anything.
+    fn intersect<T: Linear>(&self, other: &T) -> Intersection where Self: Sized {
+        let (a, r) = (self.p0(), self.direction());
+        let (b, s) = (other.p0(), other.direction());
+        let r_len = r.magnitude();
+        let s_len = s.magnitude();
+        if r_len == 0.0 || s_len == 0.0 {
+            return Intersection::None;
+        }
+        let qp = b - a;
+        let denominator = r.cross(&s);
+
+        if denominator.abs() > EPSILON * r_len * s_len {
+            let t = qp.cross(&s) / denominator;
+            let u = qp.cross(&r) / denominator;
+            if !self.contains_t(t) || !other.contains_t(u) {
+                return Intersection::None;
+            }
+            let (t0, t1) = self.t_range();
+            let (u0, u1) = other.t_range();
+            let t = t.max(t0).min(t1);
+            return Intersection::Point(Hit { point: self.point_at(t), t: t, u: u.max(u0).min(u1) });
+        }
+
+        // parallel: only collinear ones can meet.
+        if qp.cross(&r).abs() / r_len > EPSILON * (r_len + s_len + qp.magnitude()) {
+            return Intersection::None;
+        }
+        // map the other's range of u onto this one's t and intersect the two ranges.
+        let len_sq = r_len * r_len;
+        let tb0 = qp.dot(&r) / len_sq;
+        let tb1 = (b + s - a).dot(&r) / len_sq;
+        let to_t = |u: f64| if u.is_infinite() { u * (tb1 - tb0).signum() } else { tb0 + u * (tb1 - tb0) };
+        let to_u = |t: f64| (t - tb0) / (tb1 - tb0);
+        let (u0, u1) = other.t_range();
+        let (b_min, b_max) = if tb1 >= tb0 { (to_t(u0), to_t(u1)) } else { (to_t(u1), to_t(u0)) };
+        let (t0, t1) = self.t_range();
+        let low = t0.max(b_min);
+        let high = t1.min(b_max);
+        if low > high + EPSILON {
+            Intersection::None
+        }
+        else if low.is_infinite() || high.is_infinite() {
+            Intersection::Coincident
+        }
+        else if high - low <= EPSILON {
+            Intersection::Point(Hit { point: self.point_at(low), t: low, u: to_u(low) })
+        }
+        else {
+            Intersection::Overlap(Hit { point: self.point_at(low), t: low, u: to_u(low) },
+                                  Hit { point: self.point_at(high), t: high, u: to_u(high) })
+        }
+    }
+
+    // in order. a tangent touches once.
+    fn intersect_circle(&self, circle: &Circle) -> Vec<(f64, Point)> {
+        let d = self.direction();
+        let f = self.p0() - circle.center();
+        let a = d.dot(&d);
+        if a == 0.0 {
+            return Vec::new();
+        }
+        let b = 2.0 * f.dot(&d);
+        let c = f.dot(&f) - circle.r * circle.r;
+        let discriminant = b * b - 4.0 * a * c;
+        let tolerance = EPSILON * (b * b).max((4.0 * a * c).abs());
+        let ts = if discriminant.abs() <= tolerance {
+            vec![-b / (2.0 * a)]
+        }
+        else if discriminant < 0.0 {
+            Vec::new()
+        }
+        else {
+            let root = discriminant.sqrt();
+            vec![(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+        };
+        ts.into_iter()
+            .filter(|t| self.contains_t(*t))
+            .map(|t| (t, self.point_at(t)))
+            .collect()
+    }
+
+    fn intersect_rect(&self, rect: &Rect) -> Vec<(f64, Point)> where Self: Sized {
+        self.intersect_polygon(&Polygon::from_rect(rect))
+    }
+
+    // where this runs along an edge, both ends of the shared stretch are included.
+    fn intersect_polygon(&self, polygon: &Polygon) -> Vec<(f64, Point)> where Self: Sized {
+        let mut hits = Vec::new();
+        for (p0, p1) in polygon.edges() {
+            match self.intersect(&Segment::new(p0, p1)) {
+                Intersection::Point(hit) => hits.push((hit.t, hit.point)),
+                Intersection::Overlap(h0, h1) => {
+                    hits.push((h0.t, h0.point));
+                    hits.push((h1.t, h1.point));
+                }
+                _ => (),
+            }
+        }
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        // crossings through a vertex are found on both of its edges.
+        hits.dedup_by(|a, b| (a.0 - b.0).abs() <= EPSILON);
+        hits
+    }
+}
+
+impl Linear for Line {
+    fn p0(&self) -> Point { self.p0 }
+    fn p1(&self) -> Point { self.p1 }
+    fn t_range(&self) -> (f64, f64) { (-f64::INFINITY, f64::INFINITY) }
+}
+
+impl Linear for Ray {
+    fn p0(&self) -> Point { self.p0 }
+    fn p1(&self) -> Point { self.p1 }
+    fn t_range(&self) -> (f64, f64) { (0.0, f64::INFINITY) }
+}
+
+impl Linear for Segment {
+    fn p0(&self) -> Point { self.p0 }
+    fn p1(&self) -> Point { self.p1 }
+    fn t_range(&self) -> (f64, f64) { (0.0, 1.0) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    fn p(x: f64, y: f64) -> Point {
+        Point::new(x, y)
+    }
+
+    fn hit(i: Intersection) -> Hit {
+        match i {
+            Intersection::Point(h) => h,
+            other => panic!("expected a point, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_crossing() {
+        let a = Segment::new(p(0.0, 0.0), p(10.0, 0.0));
+        let b = Segment::new(p(2.0, -5.0), p(2.0, 5.0));
+        let h = hit(a.intersect(&b));
+        assert_eq!(h.point, p(2.0, 0.0));
+        assert!(approx_eq(h.t, 0.2) && approx_eq(h.u, 0.5));
+
+        // the segment stops short, but the line and ray through it don't.
+        let c = Segment::new(p(12.0, -5.0), p(12.0, 5.0));
+        assert_eq!(a.intersect(&c), Intersection::None);
+        assert!(approx_eq(hit(Line::new(a.p0, a.p1).intersect(&c)).t, 1.2));
+        assert!(approx_eq(hit(Ray::new(a.p0, a.p1).intersect(&c)).t, 1.2));
+        assert_eq!(Ray::new(a.p1, a.p0).intersect(&c), Intersection::None);
+
+        // touching end to end counts.
+        let d = Segment::new(p(10.0, 0.0), p(10.0, 5.0));
+        assert_eq!(hit(a.intersect(&d)).point, p(10.0, 0.0));
+    }
+
+    #[test]
+    fn test_parallel_and_overlap() {
+        let a = Segment::new(p(0.0, 0.0), p(10.0, 0.0));
+        assert_eq!(a.intersect(&Segment::new(p(0.0, 1.0), p(10.0, 1.0))), Intersection::None);
+        assert_eq!(a.intersect(&Segment::new(p(11.0, 0.0), p(20.0, 0.0))), Intersection::None);
+
+        match a.intersect(&Segment::new(p(15.0, 0.0), p(5.0, 0.0))) {
+            Intersection::Overlap(h0, h1) => {
+                assert_eq!(h0.point, p(5.0, 0.0));
+                assert_eq!(h1.point, p(10.0, 0.0));
+                assert!(approx_eq(h0.u, 1.0) && approx_eq(h1.u, 0.5));
+            }
+            other => panic!("expected an overlap, got {:?}", other),
+        }
+        // collinear segments meeting at a single point.
+        assert_eq!(hit(a.intersect(&Segment::new(p(10.0, 0.0), p(20.0, 0.0)))).point, p(10.0, 0.0));
+
+        let line = Line::new(p(-5.0, 0.0), p(0.0, 0.0));
+        match line.intersect(&a) {
+            Intersection::Overlap(h0, h1) => assert!(approx_eq(h0.t, 1.0) && approx_eq(h1.t, 3.0)),
+            other => panic!("expected an overlap, got {:?}", other),
+        }
+        assert_eq!(line.intersect(&Ray::new(p(3.0, 0.0), p(4.0, 0.0))), Intersection::Coincident);
+        // rays pointing away from each other share only their start.
+        let r0 = Ray::new(p(0.0, 0.0), p(1.0, 0.0));
+        let r1 = Ray::new(p(0.0, 0.0), p(-1.0, 0.0));
+        assert_eq!(hit(r0.intersect(&r1)).point, p(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_nearly_parallel() {
+        // an exact zero test would miss these being parallel.
+        let a = Segment::new(p(0.0, 0.0), p(0.3, 0.1));
+        let b = Segment::new(p(0.0, 1.0), p(0.3 * 3.0, 1.0 + 0.1 * 3.0));
+        assert_eq!(a.intersect(&b), Intersection::None);
+    }
+
+    #[test]
+    fn test_circle() {
+        let circle = Circle { x: 0.0, y: 0.0, r: 5.0 };
+        let line = Line::new(p(-10.0, 0.0), p(10.0, 0.0));
+        let hits = line.intersect_circle(&circle);
+        assert_eq!(hits.len(), 2);
+        assert!(approx_eq(hits[0].0, 0.25) && approx_eq(hits[1].0, 0.75));
+        assert!(approx_eq(hits[0].1.x, -5.0));
+        assert_eq!(Segment::new(p(0.0, 0.0), p(10.0, 0.0)).intersect_circle(&circle).len(), 1);
+        assert_eq!(Line::new(p(-10.0, 5.0), p(10.0, 5.0)).intersect_circle(&circle).len(), 1);
+        assert!(Line::new(p(-10.0, 6.0), p(10.0, 6.0)).intersect_circle(&circle).is_empty());
+    }
+
+    #[test]
+    fn test_rect_and_polygon() {
+        let rect = Rect { x: 0.0, y: 0.0, w: 10.0, h: 10.0 };
+        let hits = Line::new(p(-5.0, 5.0), p(0.0, 5.0)).intersect_rect(&rect);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].1, p(0.0, 5.0));
+        assert_eq!(hits[1].1, p(10.0, 5.0));
+        // through opposite corners, each found once.
+        assert_eq!(Line::new(p(0.0, 0.0), p(1.0, 1.0)).intersect_rect(&rect).len(), 2);
+        // along an edge gives the ends of the shared stretch.
+        let along = Segment::new(p(5.0, 0.0), p(20.0, 0.0)).intersect_rect(&rect);
+        assert_eq!(along.len(), 2);
+        assert_eq!(along[1].1, p(10.0, 0.0));
+        let triangle = Polygon::new(vec![p(0.0, 0.0), p(10.0, 0.0), p(5.0, 10.0)]);
+        assert_eq!(Ray::new(p(5.0, 5.0), p(5.0, 20.0)).intersect_polygon(&triangle).len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+extern crate cairo;
+
+use self::cairo::Context;
+use super::point::Point;
+use super::rect::Rect;
+use super::polygon::Polygon;
+use super::bezier::CubicBezier;
+
+// laid out and built up like cairo's, so rotations turn the opposite way to Point::rotate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Matrix {
+    pub xx: f64,
+    pub yx: f64,
+    pub xy: f64,
+    pub yy: f64,
+    pub x0: f64,
+    pub y0: f64,
+}
+
+// scale, then skew along x, then rotate, then translate. reflections show up as a negative y scale.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Decomposition {
+    pub translation: Point,
+    pub rotation: f64,
+    pub skew: f64,
+    pub scale: Point,
+}
+
+impl Matrix {
+    pub fn new(xx: f64, yx: f64, xy: f64, yy: f64, x0: f64, y0: f64) -> Matrix {
+        Matrix { xx: xx, yx: yx, xy: xy, yy: yy, x0: x0, y0: y0 }
+    }
+
+    pub fn identity() -> Matrix {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn from_translation(x: f64, y: f64) -> Matrix {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub fn from_rotation(angle: f64) -> Matrix {
+        let (sin, cos) = angle.sin_cos();
+        Matrix::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn from_scale(sx: f64, sy: f64) -> Matrix {
+        Matrix::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    pub fn from_skew(angle: f64) -> Matrix {
+        Matrix::new(1.0, 0.0, angle.tan(), 1.0, 0.0, 0.0)
+    }
+
+    // self and then other, as cairo_matrix_multiply.
+    pub fn then(&self, other: &Matrix) -> Matrix {
+        Matrix::new(self.xx * other.xx + self.yx * other.xy,
+                    self.xx * other.yx + self.yx * other.yy,
+                    self.xy * other.xx + self.yy * other.xy,
+                    self.xy * other.yx + self.yy * other.yy,
+                    self.x0 * other.xx + self.y0 * other.xy + other.x0,
+                    self.x0 * other.yx + self.y0 * other.yy + other.y0)
+    }
+
+    pub fn translate(&self, x: f64, y: f64) -> Matrix {
+        Matrix::from_translation(x, y).then(self)
+    }
+
+    pub fn rotate(&self, angle: f64) -> Matrix {
+        Matrix::from_rotation(angle).then(self)
+    }
+
+    pub fn scale(&self, sx: f64, sy: f64) -> Matrix {
+        Matrix::from_scale(sx, sy).then(self)
+    }
+
+    pub fn skew(&self, angle: f64) -> Matrix {
+        Matrix::from_skew(angle).then(self)
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.xx * self.yy - self.yx * self.xy
+    }
+
+    pub fn invert(&self) -> Option<Matrix> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (xx, yx, xy, yy) = (self.yy / det, -self.yx / det, -self.xy / det, self.xx / det);
+        Some(Matrix::new(xx, yx, xy, yy, -(xx * self.x0 + xy * self.y0), -(yx * self.x0 + yy * self.y0)))
+    }
+
+    pub fn decompose(&self) -> Decomposition {
+        let sx = (self.xx * self.xx + self.yx * self.yx).sqrt();
+        let rotation = self.yx.atan2(self.xx);
+        let (sy, shear) = if sx == 0.0 {
+            ((self.xy * self.xy + self.yy * self.yy).sqrt(), 0.0)
+        }
+        else {
+            // the second column, seen from the rotated x axis.
+            let (sin, cos) = rotation.sin_cos();
+            (self.determinant() / sx, self.xy * cos + self.yy * sin)
+        };
+        Decomposition {
+            translation: Point::new(self.x0, self.y0),
+            rotation: rotation,
+            skew: if sy == 0.0 { 0.0 } else { (shear / sy).atan() },
+            scale: Point::new(sx, sy),
+        }
+    }
+
+    pub fn transform_point(&self, p: &Point) -> Point {
+        Point::new(self.xx * p.x + self.xy * p.y + self.x0, self.yx * p.x + self.yy * p.y + self.y0)
+    }
+
+    // ignores translation.
+    pub fn transform_distance(&self, p: &Point) -> Point {
+        Point::new(self.xx * p.x + self.xy * p.y, self.yx * p.x + self.yy * p.y)
+    }
+
+    pub fn transform_points(&self, points: &[Point]) -> Vec<Point> {
+        points.iter().map(|p| self.transform_point(p)).collect()
+    }
+
+    pub fn transform_polygon(&self, polygon: &Polygon) -> Polygon {
+        Polygon::new(self.transform_points(&polygon.points))
+    }
+
+    pub fn transform_bezier(&self, curve: &CubicBezier) -> CubicBezier {
+        CubicBezier::new(self.transform_point(&curve.p0), self.transform_point(&curve.p1),
+                         self.transform_point(&curve.p2), self.transform_point(&curve.p3))
+    }
+
+    pub fn transform_rect(&self, rect: &Rect) -> Rect {
+        Rect::from_points(&self.transform_points(&rect.corners()))
+    }
+
+    pub fn from_cairo(matrix: &cairo::Matrix) -> Matrix {
+        Matrix::new(matrix.xx, matrix.yx, matrix.xy, matrix.yy, matrix.x0, matrix.y0)
+    }
+
+    pub fn to_cairo(&self) -> cairo::Matrix {
+        cairo::Matrix { xx: self.xx, yx: self.yx, xy: self.xy, yy: self.yy, x0: self.x0, y0: self.y0 }
+    }
+
+    pub fn from_context(context: &Context) -> Matrix {
+        Matrix::from_cairo(&context.get_matrix())
+    }
+
+    // on top of the context's current transform.
+    pub fn apply_to(&self, context: &Context) {
+        context.transform(self.to_cairo());
+    }
+}
+
+impl Decomposition {
+    pub fn to_matrix(&self) -> Matrix {
+        Matrix::from_translation(self.translation.x, self.translation.y)
+            .rotate(self.rotation)
+            .skew(self.skew)
+            .scale(self.scale.x, self.scale.y)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use math::PI;
+
+    fn near(a: &Point, b: &Point) -> bool {
+        approx_eq(a.x, b.x) && approx_eq(a.y, b.y)
+    }
+
+    fn near_matrix(a: &Matrix, b: &Matrix) -> bool {
+        approx_eq(a.xx, b.xx) && approx_eq(a.yx, b.yx) && approx_eq(a.xy, b.xy) &&
+            approx_eq(a.yy, b.yy) && approx_eq(a.x0, b.x0) && approx_eq(a.y0, b.y0)
+    }
+
+    #[test]
+    fn test_compose() {
+        // as a context would: move to 100, 50, turn a quarter, then double.
+        let m = Matrix::identity().translate(100.0, 50.0).rotate(PI / 2.0).scale(2.0, 2.0);
+        assert!(near(&m.transform_point(&Point::new(10.0, 0.0)), &Point::new(100.0, 70.0)));
+        assert!(near(&m.transform_distance(&Point::new(10.0, 0.0)), &Point::new(0.0, 20.0)));
+        // `then` runs the other way round.
+        let same = Matrix::from_scale(2.0, 2.0).then(&Matrix::from_rotation(PI / 2.0)).then(&Matrix::from_translation(100.0, 50.0));
+        assert!(near_matrix(&m, &same));
+        // cairo rotations turn the opposite way to Point::rotate.
+        let p = Point::new(3.0, 4.0);
+        assert!(near(&Matrix::from_rotation(0.3).transform_point(&p), &p.rotate(-0.3)));
+    }
+
+    #[test]
+    fn test_invert() {
+        let m = Matrix::identity().translate(12.0, -7.0).rotate(0.8).skew(0.3).scale(1.5, -0.5);
+        let inverse = m.invert().expect("not invertible");
+        assert!(near_matrix(&m.then(&inverse), &Matrix::identity()));
+        let p = Point::new(42.0, 17.0);
+        assert!(near(&inverse.transform_point(&m.transform_point(&p)), &p));
+        assert_eq!(Matrix::from_scale(1.0, 0.0).invert(), None);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let m = Matrix::identity().translate(5.0, 6.0).rotate(-2.0).skew(0.4).scale(3.0, 0.5);
+        let d = m.decompose();
+        assert!(near(&d.translation, &Point::new(5.0, 6.0)));
+        assert!(approx_eq(d.rotation, -2.0));
+        assert!(approx_eq(d.skew, 0.4));
+        assert!(near(&d.scale, &Point::new(3.0, 0.5)));
+        assert!(near_matrix(&d.to_matrix(), &m));
+        // a mirror comes out as a negative y scale.
+        let mirror = Matrix::from_scale(-1.0, 1.0);
+        assert!(near_matrix(&mirror.decompose().to_matrix(), &mirror));
+    }
+
+    #[test]
+    fn test_shapes() {
+        let m = Matrix::from_rotation(PI / 4.0);
+        let bounds = m.transform_rect(&Rect::new(0.0, 0.0, 10.0, 10.0));
+        let diagonal = 200.0f64.sqrt();
+        assert!(approx_eq(bounds.w, diagonal) && approx_eq(bounds.h, diagonal));
+        let polygon = m.transform_polygon(&Polygon::from_rect(&Rect::new(0.0, 0.0, 10.0, 10.0)));
+        assert!(approx_eq(polygon.area(), 100.0));
+        let curve = CubicBezier::new(Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(3.0, 2.0), Point::new(4.0, 0.0));
+        let moved = Matrix::from_translation(1.0, 1.0).transform_bezier(&curve);
+        assert!(near(&moved.point_at(0.5), &(curve.point_at(0.5) + Point::new(1.0, 1.0))));
+        let round_trip = Matrix::from_cairo(&m.to_cairo());
+        assert_eq!(round_trip, m);
+    }
+}
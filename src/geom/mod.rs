@@ -1,17 +1,37 @@
 pub mod point;
 pub mod rect;
 pub mod circle;
-
-use math::lerp;
+pub mod bezier;
+pub mod clip;
+pub mod corner;
+pub mod delaunay;
+pub mod ellipse;
+pub mod fit;
+pub mod hull;
+pub mod kdtree;
+pub mod offset;
+pub mod line;
+pub mod matrix;
+pub mod polygon;
+pub mod polyline;
+pub mod quadtree;
+pub mod shape;
+pub mod simplify;
+pub mod spatial_hash;
+pub mod spline;
+pub mod subdivide;
+pub mod triangle;
+pub mod triangulate;
+pub mod voronoi;
+
+use math::{ clamp, PI, TWO_PI };
+use random::Random;
 use self::point::Point;
 use self::circle::Circle;
+use self::line::{ Intersection, Linear, Segment };
 
 pub fn dot_product(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> f64 {
-    let dx0 = p1.x - p0.x;
-    let dy0 = p1.y - p0.y;
-    let dx1 = p3.x - p2.x;
-    let dy1 = p3.y - p2.y;
-    dx0 * dx1 + dy0 * dy1
+    (*p1 - *p0).dot(&(*p3 - *p2))
 }
 
 pub fn angle_between(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> f64 {
@@ -28,8 +48,18 @@ pub fn dist(p0: &Point, p1: &Point) -> f64 {
     (dx * dx + dy * dy).sqrt()
 }
 
+pub fn dist_to_segment(p: &Point, p0: &Point, p1: &Point) -> f64 {
+    let d = *p1 - *p0;
+    let len_sq = d.dot(&d);
+    if len_sq == 0.0 {
+        return dist(p, p0);
+    }
+    let t = ((*p - *p0).dot(&d) / len_sq).max(0.0).min(1.0);
+    dist(p, &p0.lerp(p1, t))
+}
+
 pub fn lerp_point(p0: Point, p1: Point, t: f64) -> Point {
-    Point::new(lerp(p0.x, p1.x, t), lerp(p0.y, p1.y, t))
+    p0.lerp(&p1, t)
 }
 
 pub fn bezier_point(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
@@ -38,10 +68,7 @@ pub fn bezier_point(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point
     let m1 = 3.0 * one_minus_t * one_minus_t * t;
     let m2 = 3.0 * one_minus_t * t * t;
     let m3 = t * t * t;
-    Point {
-        x: m0 * p0.x + m1 * p1.x + m2 * p2.x + m3 * p3.x,
-        y: m0 * p0.y + m1 * p1.y + m2 * p2.y + m3 * p3.y
-    }
+    p0 * m0 + p1 * m1 + p2 * m2 + p3 * m3
 }
 
 pub fn quadratic_point(p0: Point, p1: Point, p2: Point, t: f64) -> Point {
@@ -49,58 +76,82 @@ pub fn quadratic_point(p0: Point, p1: Point, p2: Point, t: f64) -> Point {
     let m0 = one_minus_t * one_minus_t;
     let m1 = 2.0 * one_minus_t * t;
     let m2 = t * t;
-    Point {
-        x: m0 * p0.x + m1 * p1.x + m2 * p2.x,
-        y: m0 * p0.y + m1 * p1.y + m2 * p2.y
-    }
+    p0 * m0 + p1 * m1 + p2 * m2
 }
 
+// parallel segments give None, even when they overlap.
 pub fn segment_intersect(p0: Point, p1: Point, p2: Point, p3: Point) -> Option<Point> {
-    let a1 = p1.y - p0.y;
-    let b1 = p0.x - p1.x;
-    let c1 = a1 * p0.x + b1 * p0.y;
-    let a2 = p3.y - p2.y;
-    let b2 = p2.x - p3.x;
-    let c2 = a2 * p2.x + b2 * p2.y;
-    let denominator = a1 * b2 - a2 * b1;
-
-    if denominator == 0.0 {
-        None 
+    match Segment::new(p0, p1).intersect(&Segment::new(p2, p3)) {
+        Intersection::Point(hit) => Some(hit.point),
+        _ => None,
     }
-    else {
-        let intersect_x = (b2 * c1 - b1 * c2) / denominator;
-        let intersect_y = (a1 * c2 - a2 * c1) / denominator;
-        let rx0 = (intersect_x - p0.x) / (p1.x - p0.x);
-        let ry0 = (intersect_y - p0.y) / (p1.y - p0.y);
-        let rx1 = (intersect_x - p2.x) / (p3.x - p2.x);
-        let ry1 = (intersect_y - p2.y) / (p3.y - p2.y);
-
-        if ((rx0 >= 0.0 && rx0 <= 1.0) || (ry0 >= 0.0 && ry0 <= 1.0)) &&
-           ((rx1 >= 0.0 && rx1 <= 1.0) || (ry1 >= 0.0 && ry1 <= 1.0)) {
-            Some(Point {
-                x: intersect_x,
-                y: intersect_y
-            })
-        }
-        else {
-            None
-        }
+}
+
+pub fn polygon_points(x: f64, y: f64, r: f64, sides: i32, rotation: f64) -> Vec<Point> {
+    (0..sides)
+        .map(|i| {
+            let angle = rotation + TWO_PI / sides as f64 * i as f64;
+            Point::new(x + angle.cos() * r, y + angle.sin() * r)
+        })
+        .collect()
+}
+
+pub fn star_points(x: f64, y: f64, r0: f64, r1: f64, points: i32, rotation: f64) -> Vec<Point> {
+    (0..points * 2)
+        .map(|i| {
+            let r = if i % 2 == 1 { r0 } else { r1 };
+            let angle = rotation + PI / points as f64 * i as f64;
+            Point::new(x + angle.cos() * r, y + angle.sin() * r)
+        })
+        .collect()
+}
+
+// blob outline around (x, y) for `BitContext::multi_loop`, five points per node
+pub fn splat_points(x: f64, y: f64, num_nodes: i32, radius: f64, inner_radius: f64, variation: f64, rand: &mut Random) -> Vec<Point> {
+    let mut points: Vec<Point> = Vec::new();
+    let slice = TWO_PI / (num_nodes * 2) as f64;
+    let mut angle = 0.0;
+    let curve = 0.3;
+    let radius_range = radius - inner_radius;
+    let variation = clamp(variation, 0.0, 1.0);
+    let make_point = |angle: f64, radius: f64| Point::new(x + angle.cos() * radius, y + angle.sin() * radius);
+    for _i in 0..num_nodes {
+        let radius = radius + variation * (rand.float(0.0, 1.0) * radius_range * 2.0 - radius_range);
+        let radius_range = radius - inner_radius;
+        points.push(make_point(angle - slice * (1.0 + curve), inner_radius));
+        points.push(make_point(angle + slice * curve, inner_radius));
+        points.push(make_point(angle - slice * curve, inner_radius + radius_range * 0.8));
+        points.push(make_point(angle + slice / 2.0, radius));
+        points.push(make_point(angle + slice * (1.0 + curve), inner_radius + radius_range * 0.8));
+        angle += slice * 2.0;
     }
+    points
 }
 
-pub fn tangent_point_to_circle(point: &Point, circle: &Circle, anticlockwise: bool) -> Point {
-    let d = dist(point, &circle.center());
-    let mut dir = -1.0;
-    if anticlockwise {
-        dir = 1.0;
+pub fn fractal_line_points(p0: Point, p1: Point, roughness: f64, iterations: i32, rand: &mut Random) -> Vec<Point> {
+    let mut offset = dist(&p0, &p1) * 0.15;
+    let mut path = vec![p0, p1];
+    for _i in 0..iterations {
+        let mut new_path: Vec<Point> = Vec::new();
+        for (j, point) in path.iter().enumerate() {
+            new_path.push(*point);
+            if j < path.len() - 1 {
+                let x = (point.x + path[j + 1].x) / 2.0 + rand.float(0.0, 1.0) * offset * 2.0 - offset;
+                let y = (point.y + path[j + 1].y) / 2.0 + rand.float(0.0, 1.0) * offset * 2.0 - offset;
+                new_path.push(Point::new(x, y));
+            }
+        }
+        offset *= roughness;
+        path = new_path;
     }
-    let angle = (-circle.r / d).cos() * dir;
-    let base_angle = (circle.y - point.y).atan2(circle.x - point.x);
-    let total_angle = base_angle + angle;
+    path
+}
 
-    Point {
-        x: circle.x + (total_angle).cos() * circle.r,
-        y: circle.y + (total_angle).sin() * circle.r
+// the anticlockwise one on screen from the center if anticlockwise is set. points inside give the nearest point on the circle.
+pub fn tangent_point_to_circle(point: &Point, circle: &Circle, anticlockwise: bool) -> Point {
+    match circle.tangent_points(point) {
+        Some((a, c)) => if anticlockwise { a } else { c },
+        None => circle.point_at_angle(circle.center().angle_to(point)),
     }
 }
 
@@ -109,6 +160,7 @@ pub fn tangent_point_to_circle(point: &Point, circle: &Circle, anticlockwise: bo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use util::approx_eq;
 
     #[test]
     fn test_intersect() {
@@ -126,6 +178,44 @@ mod tests {
         let p2 = Point::new(20.0, -10.0);
         let p3 = Point::new(20.0,  10.0);
         assert_eq!(segment_intersect(p0, p1, p2, p3), None);
+        // collinear overlapping segments don't cross at any one point.
+        assert_eq!(segment_intersect(p0, p1, Point::new(0.0, 0.0), Point::new(20.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_tangent_point() {
+        let circle = Circle::new(0.0, 0.0, 5.0);
+        let p = Point::new(0.0, 10.0);
+        for &anticlockwise in [true, false].iter() {
+            let t = tangent_point_to_circle(&p, &circle, anticlockwise);
+            assert!(approx_eq(dist(&t, &circle.center()), 5.0));
+            assert!(approx_eq(t.dot(&(p - t)), 0.0));
+        }
+        assert!(tangent_point_to_circle(&p, &circle, true).x > 0.0);
+    }
+
+    #[test]
+    fn test_seeded_splat() {
+        let points = splat_points(50.0, 50.0, 7, 40.0, 20.0, 0.5, &mut Random::from_seed(3));
+        assert_eq!(points.len(), 35);
+        assert_eq!(points, splat_points(50.0, 50.0, 7, 40.0, 20.0, 0.5, &mut Random::from_seed(3)));
+        assert!(points != splat_points(50.0, 50.0, 7, 40.0, 20.0, 0.5, &mut Random::from_seed(4)));
+        for p in &points {
+            let d = dist(p, &Point::new(50.0, 50.0));
+            assert!(d >= 20.0 - 1e-9 && d <= 60.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_seeded_fractal_line() {
+        let p0 = Point::new(0.0, 0.0);
+        let p1 = Point::new(100.0, 0.0);
+        let points = fractal_line_points(p0, p1, 0.5, 4, &mut Random::from_seed(3));
+        assert_eq!(points.len(), 17);
+        assert_eq!(points[0], p0);
+        assert_eq!(points[16], p1);
+        assert_eq!(points, fractal_line_points(p0, p1, 0.5, 4, &mut Random::from_seed(3)));
+        assert!(points != fractal_line_points(p0, p1, 0.5, 4, &mut Random::from_seed(4)));
     }
 
 }
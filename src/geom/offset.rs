@@ -0,0 +1,350 @@
+use super::point::Point;
+use super::polygon::Polygon;
+use super::dist_to_segment;
+use std::cmp::Ordering;
+use math::{ PI, TWO_PI };
+
+// miter takes a limit on the miter length as a multiple of the offset distance.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Join {
+    Miter(f64),
+    Round,
+    Bevel,
+}
+
+// negative distances shrink, which can split the shape into several rings or none.
+pub fn offset(points: &[Point], distance: f64, join: Join) -> Vec<Vec<Point>> {
+    let points = dedupe(points);
+    if points.len() < 3 || !distance.is_finite() {
+        return Vec::new();
+    }
+    let original = Polygon::new(points.clone());
+    let orientation = original.signed_area().signum();
+    if orientation == 0.0 {
+        return Vec::new();
+    }
+    if distance == 0.0 {
+        return vec![points];
+    }
+
+    let raw = dedupe(&raw_offset(&points, distance * orientation, distance, join));
+    // where the offset folds over itself, only the parts it winds around the same
+    // way as the original are kept. genuine rings are built from offset points that
+    // stay at least `distance` from every edge. crossing points are left out of that
+    // test as they can fall on bevels and arc chords, just inside the distance.
+    let min_area = distance * distance * 1e-6;
+    let min_dist = distance.abs() * 0.99;
+    let edges = original.edges();
+    winding_rings(&raw, orientation as i32)
+        .into_iter()
+        .filter(|ring| {
+            Polygon::from_slice(ring).signed_area() * orientation > min_area &&
+                ring.iter()
+                    .filter(|p| raw.contains(p))
+                    .all(|p| edges.iter().all(|&(a, b)| dist_to_segment(p, &a, &b) >= min_dist))
+        })
+        .collect()
+}
+
+pub fn inset(points: &[Point], distance: f64, join: Join) -> Vec<Vec<Point>> {
+    offset(points, -distance, join)
+}
+
+pub const MAX_CONTOURS: usize = 1000;
+
+// each inset is measured from the original path so errors don't accumulate.
+pub fn contours(points: &[Point], step: f64, join: Join) -> Vec<Vec<Point>> {
+    let mut result = Vec::new();
+    if step <= 0.0 || !step.is_finite() || points.is_empty() {
+        return result;
+    }
+    // nothing survives an inset of half the narrower side of the bounds.
+    let bounds = Polygon::from_slice(points).bounds();
+    let steps = (bounds.w.min(bounds.h) / 2.0 / step).ceil();
+    if steps.is_nan() {
+        return result;
+    }
+    for i in 1..(steps as usize).min(MAX_CONTOURS) + 1 {
+        let rings = inset(points, step * i as f64, join);
+        if rings.is_empty() {
+            break;
+        }
+        result.extend(rings);
+    }
+    result
+}
+
+// offsets each edge along its outward normal and joins neighbours.
+// `normal_distance` is signed so that positive always moves to the right of each edge
+// direction; `distance` keeps the caller's sign to tell growing from shrinking.
+fn raw_offset(points: &[Point], normal_distance: f64, distance: f64, join: Join) -> Vec<Point> {
+    let n = points.len();
+    let normals: Vec<Point> = (0..n)
+        .map(|i| -(points[(i + 1) % n] - points[i]).normalize().perpendicular())
+        .collect();
+    let radius = distance.abs();
+    let mut result = Vec::new();
+    for i in 0..n {
+        let p = points[i];
+        let prev = points[(i + n - 1) % n];
+        let next = points[(i + 1) % n];
+        let n0 = normals[(i + n - 1) % n];
+        let n1 = normals[i];
+        let end0 = p + n0 * normal_distance;
+        let start1 = p + n1 * normal_distance;
+        let turn = (p - prev).cross(&(next - p));
+        let straight = turn.abs() < 1e-12 * (p - prev).magnitude() * (next - p).magnitude();
+
+        if straight && (p - prev).dot(&(next - p)) > 0.0 {
+            result.push(start1);
+            continue;
+        }
+        // the offset lines pull apart on the outside of a corner and cross on the inside.
+        let outer = turn * normal_distance > 0.0 || straight;
+        if !outer {
+            // going back through the corner keeps the winding right where
+            // the offset edges overshoot each other.
+            result.push(end0);
+            result.push(p);
+            result.push(start1);
+            continue;
+        }
+        match join {
+            Join::Miter(limit) => {
+                let miter = miter_point(&p, &n0, &n1, normal_distance);
+                if miter.dist(&p) <= limit * radius && !straight {
+                    result.push(miter);
+                }
+                else {
+                    result.push(end0);
+                    result.push(start1);
+                }
+            }
+            Join::Bevel => {
+                result.push(end0);
+                result.push(start1);
+            }
+            Join::Round => {
+                let start_angle = p.angle_to(&end0);
+                let mut sweep = p.angle_to(&start1) - start_angle;
+                while sweep > PI {
+                    sweep -= TWO_PI;
+                }
+                while sweep < -PI {
+                    sweep += TWO_PI;
+                }
+                let segments = ((sweep.abs() / 0.15).ceil() as i32).max(1);
+                for j in 0..segments + 1 {
+                    let angle = start_angle + sweep * j as f64 / segments as f64;
+                    result.push(p + Point::from_polar(angle, radius));
+                }
+            }
+        }
+    }
+    result
+}
+
+// where the two offset edges meet, moving along the bisector of their normals.
+fn miter_point(corner: &Point, n0: &Point, n1: &Point, normal_distance: f64) -> Point {
+    let cos = n0.dot(n1);
+    if cos < -1.0 + 1e-9 {
+        return *corner + *n0 * normal_distance;
+    }
+    *corner + (*n0 + *n1) * (normal_distance / (1.0 + cos))
+}
+
+// the outline of the area a self intersecting ring winds around at least once in
+// the direction of `sign`. the ring is cut at every crossing, pieces with that area
+// on one side only are kept, and the pieces are chained back into rings.
+fn winding_rings(ring: &[Point], sign: i32) -> Vec<Vec<Point>> {
+    let n = ring.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    let mut cuts: Vec<Vec<(f64, Point)>> = (0..n).map(|i| vec![(0.0, ring[i]), (1.0, ring[(i + 1) % n])]).collect();
+    for i in 0..n {
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let (p0, p1, p2, p3) = (ring[i], ring[i + 1], ring[j], ring[(j + 1) % n]);
+            if let Some((t, u)) = crossing(&p0, &p1, &p2, &p3) {
+                // snap touches to the existing vertex so the pieces still join up exactly.
+                let eps = 1e-9;
+                let x = if t <= eps { p0 }
+                        else if t >= 1.0 - eps { p1 }
+                        else if u <= eps { p2 }
+                        else if u >= 1.0 - eps { p3 }
+                        else { p0.lerp(&p1, t) };
+                cuts[i].push((t, x));
+                cuts[j].push((u, x));
+            }
+        }
+    }
+
+    let polygon = Polygon::from_slice(ring);
+    let inside = |p: &Point| polygon.winding_number(p) * sign >= 1;
+    let mut pieces = Vec::new();
+    for cut in cuts.iter_mut() {
+        cut.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        for k in 0..cut.len() - 1 {
+            let (a, b) = (cut[k].1, cut[k + 1].1);
+            if a == b {
+                continue;
+            }
+            let mid = a.lerp(&b, 0.5);
+            let side = (b - a).normalize().perpendicular() * (1e-7 * (1.0 + mid.magnitude()));
+            if inside(&(mid + side)) != inside(&(mid - side)) {
+                pieces.push((a, b));
+            }
+        }
+    }
+
+    let mut used = vec![false; pieces.len()];
+    let mut result = Vec::new();
+    for start in 0..pieces.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut loop_points = vec![pieces[start].0];
+        let mut end = pieces[start].1;
+        while end != pieces[start].0 {
+            match (0..pieces.len()).find(|&k| !used[k] && pieces[k].0 == end) {
+                Some(k) => {
+                    used[k] = true;
+                    loop_points.push(end);
+                    end = pieces[k].1;
+                }
+                None => break,
+            }
+        }
+        if end == pieces[start].0 && loop_points.len() > 2 {
+            result.push(loop_points);
+        }
+    }
+    result
+}
+
+// the parameters along p0-p1 and p2-p3 where they cross or touch.
+fn crossing(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> Option<(f64, f64)> {
+    let r = *p1 - *p0;
+    let s = *p3 - *p2;
+    let d = r.cross(&s);
+    if d.abs() < 1e-12 {
+        return None;
+    }
+    let t = (*p2 - *p0).cross(&s) / d;
+    let u = (*p2 - *p0).cross(&r) / d;
+    let eps = 1e-9;
+    if t >= -eps && t <= 1.0 + eps && u >= -eps && u <= 1.0 + eps {
+        Some((t.max(0.0).min(1.0), u.max(0.0).min(1.0)))
+    }
+    else {
+        None
+    }
+}
+
+fn dedupe(points: &[Point]) -> Vec<Point> {
+    let mut result: Vec<Point> = Vec::new();
+    for p in points.iter() {
+        if result.last().map_or(true, |last| last != p) {
+            result.push(*p);
+        }
+    }
+    while result.len() > 1 && result[0] == result[result.len() - 1] {
+        result.pop();
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::rect::Rect;
+    use geom::star_points;
+
+    fn area(ring: &[Point]) -> f64 {
+        Polygon::from_slice(ring).area()
+    }
+
+    fn near(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn test_grow() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec();
+        for points in [square.clone(), square.iter().rev().cloned().collect()].iter() {
+            let miter = offset(points, 10.0, Join::Miter(4.0));
+            assert_eq!(miter.len(), 1);
+            assert!(near(area(&miter[0]), 14400.0, 1e-6));
+            let bevel = offset(points, 10.0, Join::Bevel);
+            assert!(near(area(&bevel[0]), 14200.0, 1e-6));
+            let round = offset(points, 10.0, Join::Round);
+            assert!(near(area(&round[0]), 14000.0 + PI * 100.0, 2.0));
+        }
+    }
+
+    #[test]
+    fn test_miter_limit() {
+        // a sharp spike exceeds the miter limit and gets bevelled.
+        let spike = vec![Point::new(0.0, 0.0), Point::new(100.0, 45.0), Point::new(0.0, 55.0)];
+        let limited = offset(&spike, 5.0, Join::Miter(2.0));
+        let unlimited = offset(&spike, 5.0, Join::Miter(100.0));
+        assert_eq!(limited[0].len(), unlimited[0].len() + 1);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec();
+        let rings = inset(&square, 10.0, Join::Miter(4.0));
+        assert_eq!(rings.len(), 1);
+        assert!(near(area(&rings[0]), 6400.0, 1e-6));
+        assert!(inset(&square, 50.0, Join::Miter(4.0)).is_empty());
+        assert!(inset(&square, 60.0, Join::Round).is_empty());
+    }
+
+    #[test]
+    fn test_shrink_splits() {
+        // two squares joined by a thin bridge come apart when inset past the bridge.
+        let dumbbell = vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 40.0), Point::new(200.0, 40.0),
+                            Point::new(200.0, 0.0), Point::new(300.0, 0.0), Point::new(300.0, 100.0), Point::new(200.0, 100.0),
+                            Point::new(200.0, 60.0), Point::new(100.0, 60.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)];
+        assert_eq!(inset(&dumbbell, 5.0, Join::Miter(4.0)).len(), 1);
+        let rings = inset(&dumbbell, 15.0, Join::Miter(4.0));
+        assert_eq!(rings.len(), 2);
+        assert!(near(area(&rings[0]), 4900.0, 1e-6));
+        assert!(near(area(&rings[1]), 4900.0, 1e-6));
+    }
+
+    #[test]
+    fn test_shrink_star() {
+        // the arms of a star fold over themselves well before its middle vanishes.
+        let star = star_points(0.0, 0.0, 40.0, 100.0, 7, 0.1);
+        let edges = Polygon::from_slice(&star).edges();
+        for &join in [Join::Miter(4.0), Join::Round, Join::Bevel].iter() {
+            let rings = inset(&star, 20.0, join);
+            assert_eq!(rings.len(), 1);
+            for p in rings[0].iter() {
+                assert!(edges.iter().all(|&(a, b)| dist_to_segment(p, &a, &b) > 20.0 * 0.9));
+            }
+        }
+        assert!(inset(&star, 45.0, Join::Round).is_empty());
+    }
+
+    #[test]
+    fn test_contours() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec();
+        let rings = contours(&square, 10.0, Join::Miter(4.0));
+        assert_eq!(rings.len(), 4);
+        assert!(near(area(&rings[3]), 400.0, 1e-6));
+
+        assert!(contours(&square, f64::NAN, Join::Bevel).is_empty());
+        assert!(contours(&square, f64::INFINITY, Join::Bevel).is_empty());
+        assert!(contours(&square, 0.0, Join::Bevel).is_empty());
+        assert_eq!(contours(&square, 0.01, Join::Bevel).len(), MAX_CONTOURS);
+        assert!(offset(&square, f64::NAN, Join::Round).is_empty());
+    }
+}
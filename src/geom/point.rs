@@ -1,5 +1,6 @@
-#[derive(PartialEq)]
-#[derive(Debug)]
+use std::ops::{ Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign };
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
@@ -34,14 +35,134 @@ impl Point {
         Point::new(self.x * scale_x, self.y * scale_y)
     }
 
-    pub fn rotate(&self, angle: &f64) -> Point {
+    // positive angles turn the opposite way to from_polar and cairo's rotate.
+    pub fn rotate(&self, angle: f64) -> Point {
         let x = self.x * angle.cos() + self.y * angle.sin();
         let y = self.y * angle.cos() - self.x * angle.sin();
         Point::new(x, y)
     }
 
-    pub fn clone(&self) -> Point {
-        Point::new(self.x, self.y)
+    pub fn rotate_around(&self, center: &Point, angle: f64) -> Point {
+        (*self - *center).rotate(angle) + *center
+    }
+
+    // the zero vector stays zero.
+    pub fn normalize(&self) -> Point {
+        let mag = self.magnitude();
+        if mag == 0.0 {
+            *self
+        }
+        else {
+            *self / mag
+        }
+    }
+
+    pub fn dot(&self, other: &Point) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // positive when other is clockwise from self on screen.
+    pub fn cross(&self, other: &Point) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    // a quarter turn clockwise on screen.
+    pub fn perpendicular(&self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+
+    pub fn lerp(&self, other: &Point, t: f64) -> Point {
+        *self + (*other - *self) * t
+    }
+
+    pub fn angle_to(&self, other: &Point) -> f64 {
+        (*other - *self).angle()
+    }
+
+    pub fn project_onto(&self, other: &Point) -> Point {
+        let len_sq = other.dot(other);
+        if len_sq == 0.0 {
+            Point::new(0.0, 0.0)
+        }
+        else {
+            *other * (self.dot(other) / len_sq)
+        }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, scale: f64) -> Point {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Mul<Point> for f64 {
+    type Output = Point;
+
+    fn mul(self, point: Point) -> Point {
+        point * self
+    }
+}
+
+impl Div<f64> for Point {
+    type Output = Point;
+
+    fn div(self, scale: f64) -> Point {
+        Point::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl MulAssign<f64> for Point {
+    fn mul_assign(&mut self, scale: f64) {
+        self.x *= scale;
+        self.y *= scale;
+    }
+}
+
+impl DivAssign<f64> for Point {
+    fn div_assign(&mut self, scale: f64) {
+        self.x /= scale;
+        self.y /= scale;
     }
 }
 
@@ -124,6 +245,53 @@ mod tests {
         let point_b = Point::new(25.0, 20.0); 
         assert_eq!(point_a.dist(&point_b), 50.0);
     }
+
+    #[test]
+    fn test_operators() {
+        let a = Point::new(1.0, 2.0);
+        let b = Point::new(3.0, -4.0);
+        assert_eq!(a + b, Point::new(4.0, -2.0));
+        assert_eq!(a - b, Point::new(-2.0, 6.0));
+        assert_eq!(a * 2.0, Point::new(2.0, 4.0));
+        assert_eq!(2.0 * a, Point::new(2.0, 4.0));
+        assert_eq!(b / 2.0, Point::new(1.5, -2.0));
+        assert_eq!(-a, Point::new(-1.0, -2.0));
+        let mut c = a;
+        c += b;
+        assert_eq!(c, Point::new(4.0, -2.0));
+        c -= a;
+        assert_eq!(c, b);
+        c *= 2.0;
+        assert_eq!(c, Point::new(6.0, -8.0));
+        c /= 4.0;
+        assert_eq!(c, Point::new(1.5, -2.0));
+    }
+
+    #[test]
+    fn test_vector_methods() {
+        let a = Point::new(3.0, 4.0);
+        let n = a.normalize();
+        assert!(almost_eq(n.x, 0.6));
+        assert!(almost_eq(n.y, 0.8));
+        assert_eq!(Point::new(0.0, 0.0).normalize(), Point::new(0.0, 0.0));
+        assert_eq!(a.dot(&Point::new(2.0, 1.0)), 10.0);
+        assert_eq!(Point::new(1.0, 0.0).cross(&Point::new(0.0, 1.0)), 1.0);
+        assert_eq!(a.perpendicular(), Point::new(-4.0, 3.0));
+        assert_eq!(a.perpendicular().dot(&a), 0.0);
+        assert_eq!(a.lerp(&Point::new(5.0, 8.0), 0.5), Point::new(4.0, 6.0));
+        assert_eq!(Point::new(1.0, 1.0).angle_to(&Point::new(1.0, 5.0)), PI / 2.0);
+        assert_eq!(a.project_onto(&Point::new(10.0, 0.0)), Point::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let p = Point::new(10.0, 0.0).rotate(PI / 2.0);
+        assert!(almost_eq(p.x, 0.0));
+        assert!(almost_eq(p.y, -10.0));
+        let p = Point::new(20.0, 10.0).rotate_around(&Point::new(10.0, 10.0), PI / 2.0);
+        assert!(almost_eq(p.x, 10.0));
+        assert!(almost_eq(p.y, 0.0));
+    }
 }
 
 
@@ -0,0 +1,258 @@
+use super::point::Point;
+use super::rect::Rect;
+use super::{ polygon_points, star_points };
+use super::clip;
+use math::TWO_PI;
+
+// the last point connects back to the first, so it should not be repeated.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polygon {
+    pub points: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point>) -> Polygon {
+        Polygon { points: points }
+    }
+
+    pub fn from_slice(points: &[Point]) -> Polygon {
+        Polygon::new(points.to_vec())
+    }
+
+    pub fn from_rect(rect: &Rect) -> Polygon {
+        Polygon::new(vec![Point::new(rect.x, rect.y),
+                          Point::new(rect.x + rect.w, rect.y),
+                          Point::new(rect.x + rect.w, rect.y + rect.h),
+                          Point::new(rect.x, rect.y + rect.h)])
+    }
+
+    pub fn regular(x: f64, y: f64, r: f64, sides: i32, rotation: f64) -> Polygon {
+        Polygon::new(polygon_points(x, y, r, sides, rotation))
+    }
+
+    pub fn star(x: f64, y: f64, r0: f64, r1: f64, points: i32, rotation: f64) -> Polygon {
+        Polygon::new(star_points(x, y, r0, r1, points, rotation))
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    // including the closing edge.
+    pub fn edges(&self) -> Vec<(Point, Point)> {
+        let n = self.points.len();
+        (0..n).map(|i| (self.points[i], self.points[(i + 1) % n])).collect()
+    }
+
+    // positive when the points run clockwise on screen (y down).
+    pub fn signed_area(&self) -> f64 {
+        self.edges().iter().fold(0.0, |sum, &(p0, p1)| sum + p0.cross(&p1)) / 2.0
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        if self.points.len() < 2 {
+            return 0.0;
+        }
+        self.edges().iter().fold(0.0, |sum, &(p0, p1)| sum + p0.dist(&p1))
+    }
+
+    // falls back to the average of the points for degenerate polygons.
+    pub fn centroid(&self) -> Point {
+        let area = self.signed_area();
+        if area == 0.0 {
+            let n = self.points.len().max(1) as f64;
+            return self.points.iter().fold(Point::new(0.0, 0.0), |sum, p| sum + *p) / n;
+        }
+        let sum = self.edges().iter().fold(Point::new(0.0, 0.0), |sum, &(p0, p1)| sum + (p0 + p1) * p0.cross(&p1));
+        sum / (6.0 * area)
+    }
+
+    pub fn is_clockwise(&self) -> bool {
+        self.signed_area() > 0.0
+    }
+
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+    }
+
+    pub fn reversed(&self) -> Polygon {
+        let mut polygon = self.clone();
+        polygon.reverse();
+        polygon
+    }
+
+    pub fn clockwise(&self) -> Polygon {
+        if self.signed_area() < 0.0 { self.reversed() } else { self.clone() }
+    }
+
+    pub fn anticlockwise(&self) -> Polygon {
+        if self.signed_area() > 0.0 { self.reversed() } else { self.clone() }
+    }
+
+    // every turn the same way and winding once round. straight runs are allowed.
+    pub fn is_convex(&self) -> bool {
+        let n = self.points.len();
+        if n < 3 {
+            return false;
+        }
+        let mut sign = 0.0;
+        let mut turning = 0.0;
+        for i in 0..n {
+            let p0 = self.points[i];
+            let p1 = self.points[(i + 1) % n];
+            let p2 = self.points[(i + 2) % n];
+            let cross = (p1 - p0).cross(&(p2 - p1));
+            if cross != 0.0 {
+                if sign != 0.0 && cross.signum() != sign {
+                    return false;
+                }
+                sign = cross.signum();
+            }
+            turning += cross.atan2((p1 - p0).dot(&(p2 - p1)));
+        }
+        // a star turns the same way at every point but goes round more than once.
+        sign != 0.0 && (turning.abs() - TWO_PI).abs() < 1e-6
+    }
+
+    // even-odd rule.
+    pub fn contains_point(&self, p: &Point) -> bool {
+        let mut inside = false;
+        for (p0, p1) in self.edges() {
+            if (p0.y > p.y) != (p1.y > p.y) &&
+               p.x < p0.x + (p.y - p0.y) / (p1.y - p0.y) * (p1.x - p0.x) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    // nonzero rule, so overlapping loops count as inside.
+    pub fn contains_point_nonzero(&self, p: &Point) -> bool {
+        self.winding_number(p) != 0
+    }
+
+    // clockwise loops on screen count positive.
+    pub fn winding_number(&self, p: &Point) -> i32 {
+        let mut winding = 0;
+        for (p0, p1) in self.edges() {
+            let side = (p1 - p0).cross(&(*p - p0));
+            if p0.y <= p.y {
+                if p1.y > p.y && side < 0.0 {
+                    winding -= 1;
+                }
+            }
+            else if p1.y <= p.y && side > 0.0 {
+                winding += 1;
+            }
+        }
+        winding
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::from_points(&self.points)
+    }
+
+    pub fn translate(&self, x: f64, y: f64) -> Polygon {
+        Polygon::new(self.points.iter().map(|p| p.translate(x, y)).collect())
+    }
+
+    pub fn union(&self, other: &Polygon) -> Vec<Polygon> {
+        clip::union(self, other)
+    }
+
+    pub fn intersection(&self, other: &Polygon) -> Vec<Polygon> {
+        clip::intersection(self, other)
+    }
+
+    pub fn difference(&self, other: &Polygon) -> Vec<Polygon> {
+        clip::difference(self, other)
+    }
+
+    pub fn xor(&self, other: &Polygon) -> Vec<Polygon> {
+        clip::xor(self, other)
+    }
+
+    pub fn clip_to_convex(&self, clip: &Polygon) -> Polygon {
+        clip::clip_to_convex(self, clip)
+    }
+
+    pub fn clip_to_rect(&self, rect: &Rect) -> Polygon {
+        clip::clip_to_rect(self, rect)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use math::PI;
+
+    #[test]
+    fn test_area_and_winding() {
+        let p = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert_eq!(p.signed_area(), 10000.0);
+        assert!(p.is_clockwise());
+        assert_eq!(p.reversed().signed_area(), -10000.0);
+        assert!(!p.reversed().is_clockwise());
+        assert_eq!(p.reversed().clockwise(), p);
+        assert_eq!(p.area(), 10000.0);
+        assert_eq!(p.perimeter(), 400.0);
+    }
+
+    #[test]
+    fn test_centroid() {
+        let square = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert_eq!(square.centroid(), Point::new(50.0, 50.0));
+        let tri = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(90.0, 0.0), Point::new(0.0, 30.0)]);
+        let c = tri.centroid();
+        assert!(approx_eq(c.x, 30.0));
+        assert!(approx_eq(c.y, 10.0));
+        assert_eq!(tri.reversed().centroid(), c);
+    }
+
+    #[test]
+    fn test_convex() {
+        let square = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert!(square.is_convex());
+        assert!(Polygon::regular(0.0, 0.0, 50.0, 7, 0.3).is_convex());
+        let arrow = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(50.0, 20.0), Point::new(100.0, 0.0), Point::new(50.0, 100.0)]);
+        assert!(!arrow.is_convex());
+        let star = Polygon::new((0..5).map(|i| Point::from_polar(i as f64 * 4.0 * PI / 5.0, 100.0)).collect());
+        assert!(!star.is_convex());
+        assert!(!Polygon::star(0.0, 0.0, 40.0, 100.0, 5, 0.0).is_convex());
+    }
+
+    #[test]
+    fn test_contains() {
+        let p = Polygon::from_rect(&Rect::new(0.0, 0.0, 100.0, 100.0));
+        assert!(p.contains_point(&Point::new(50.0, 50.0)));
+        assert!(!p.contains_point(&Point::new(150.0, 50.0)));
+        assert_eq!(p.winding_number(&Point::new(50.0, 50.0)), 1);
+        assert_eq!(p.reversed().winding_number(&Point::new(50.0, 50.0)), -1);
+
+        // a pentagram: the center is outside by even-odd but inside by nonzero.
+        let star = Polygon::new((0..5).map(|i| Point::from_polar(i as f64 * 4.0 * PI / 5.0, 100.0)).collect());
+        let center = Point::new(0.0, 0.0);
+        assert!(!star.contains_point(&center));
+        assert!(star.contains_point_nonzero(&center));
+        assert_eq!(star.winding_number(&center).abs(), 2);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let b = Polygon::regular(10.0, 20.0, 5.0, 4, 0.0).bounds();
+        assert!(approx_eq(b.x, 5.0));
+        assert!(approx_eq(b.y, 15.0));
+        assert!(approx_eq(b.w, 10.0));
+        assert!(approx_eq(b.h, 10.0));
+    }
+}
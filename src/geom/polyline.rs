@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use super::point::Point;
+
+// closed polylines run from the last point back to the first.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Polyline {
+    points: Vec<Point>,
+    lengths: Vec<f64>,
+    closed: bool,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point>) -> Polyline {
+        Polyline::build(points, false)
+    }
+
+    pub fn closed(points: Vec<Point>) -> Polyline {
+        Polyline::build(points, true)
+    }
+
+    fn build(points: Vec<Point>, closed: bool) -> Polyline {
+        let mut lengths = Vec::with_capacity(points.len() + 1);
+        let mut total = 0.0;
+        for i in 0..points.len() {
+            lengths.push(total);
+            if i + 1 < points.len() {
+                total += points[i].dist(&points[i + 1]);
+            }
+        }
+        if closed && !points.is_empty() {
+            lengths.push(total + points[points.len() - 1].dist(&points[0]));
+        }
+        Polyline {
+            points: points,
+            lengths: lengths,
+            closed: closed,
+        }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn length(&self) -> f64 {
+        self.lengths.last().cloned().unwrap_or(0.0)
+    }
+
+    pub fn lengths(&self) -> &[f64] {
+        &self.lengths[..self.points.len()]
+    }
+
+    // open paths clamp to their ends, closed paths wrap around. distances that can't be placed give the first point.
+    pub fn point_at(&self, distance: f64) -> Point {
+        match self.segment_at(distance) {
+            Some((i, t)) => {
+                let (p0, p1) = self.segment(i);
+                p0.lerp(&p1, t)
+            }
+            None => self.points.first().cloned().unwrap_or(Point::new(0.0, 0.0)),
+        }
+    }
+
+    pub fn tangent_at(&self, distance: f64) -> Point {
+        match self.segment_at(distance) {
+            Some((i, _)) => {
+                let (p0, p1) = self.segment(i);
+                (p1 - p0).normalize()
+            }
+            None => Point::new(0.0, 0.0),
+        }
+    }
+
+    // a quarter turn clockwise on screen from the tangent.
+    pub fn normal_at(&self, distance: f64) -> Point {
+        self.tangent_at(distance).perpendicular()
+    }
+
+    // open paths include both ends, closed paths don't repeat the first point.
+    pub fn resample(&self, count: usize) -> Vec<Point> {
+        if count == 0 || self.points.is_empty() {
+            return Vec::new();
+        }
+        if count == 1 {
+            return vec![self.points[0]];
+        }
+        let spans = if self.closed { count } else { count - 1 };
+        let step = self.length() / spans as f64;
+        (0..count).map(|i| self.point_at(step * i as f64)).collect()
+    }
+
+    // open paths also keep their end point, so the last gap can be shorter.
+    pub fn resample_spacing(&self, spacing: f64) -> Vec<Point> {
+        if self.points.is_empty() {
+            return Vec::new();
+        }
+        if spacing <= 0.0 {
+            return vec![self.points[0]];
+        }
+        let length = self.length();
+        let mut result = Vec::new();
+        let mut d = 0.0;
+        while d < length - 1e-9 {
+            result.push(self.point_at(d));
+            d += spacing;
+        }
+        if !self.closed || result.is_empty() {
+            result.push(self.point_at(length));
+        }
+        result
+    }
+
+    // a closed path is cut open at its first point.
+    pub fn split_at(&self, distance: f64) -> (Polyline, Polyline) {
+        let (i, t) = match self.segment_at(distance) {
+            Some(found) => found,
+            None => return (Polyline::new(self.points.clone()), Polyline::new(self.points.clone())),
+        };
+        let mut points = self.points.clone();
+        if self.closed {
+            points.push(self.points[0]);
+        }
+        let p = self.point_at(distance);
+        let mut first = points[..i + 1].to_vec();
+        let mut second = vec![p];
+        if t > 0.0 {
+            first.push(p);
+        }
+        if t < 1.0 {
+            second.push(points[i + 1]);
+        }
+        second.extend_from_slice(&points[i + 2..]);
+        (Polyline::new(first), Polyline::new(second))
+    }
+
+    fn segment_count(&self) -> usize {
+        self.lengths.len() - 1
+    }
+
+    fn segment(&self, i: usize) -> (Point, Point) {
+        (self.points[i], self.points[(i + 1) % self.points.len()])
+    }
+
+    // the segment holding `distance` and how far along it, by binary search on the lengths.
+    // zero length segments are skipped so tangents are always defined where possible.
+    // NaN has no place on any path, and infinity has none on a closed one.
+    fn segment_at(&self, distance: f64) -> Option<(usize, f64)> {
+        if self.points.len() < 2 || distance.is_nan() || (self.closed && distance.is_infinite()) {
+            return None;
+        }
+        let length = self.length();
+        let distance = if self.closed && length > 0.0 {
+            distance - (distance / length).floor() * length
+        }
+        else {
+            distance.max(0.0).min(length)
+        };
+        let count = self.segment_count();
+        let mut i = match self.lengths.binary_search_by(|l| l.partial_cmp(&distance).unwrap_or(Ordering::Less)) {
+            Ok(i) => i,
+            Err(i) => i.max(1) - 1,
+        };
+        i = i.min(count - 1);
+        while i + 1 < count && self.lengths[i + 1] <= self.lengths[i] {
+            i += 1;
+        }
+        while i > 0 && self.lengths[i + 1] <= self.lengths[i] {
+            i -= 1;
+        }
+        let span = self.lengths[i + 1] - self.lengths[i];
+        let t = if span > 0.0 { ((distance - self.lengths[i]) / span).max(0.0).min(1.0) } else { 0.0 };
+        Some((i, t))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use geom::rect::Rect;
+
+    fn ell() -> Polyline {
+        Polyline::new(vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 50.0)])
+    }
+
+    #[test]
+    fn test_length_and_point_at() {
+        let square = Polyline::closed(Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec());
+        let line = ell();
+        assert_eq!(line.length(), 150.0);
+        assert_eq!(line.lengths(), &[0.0, 100.0, 150.0]);
+        assert_eq!(line.point_at(50.0), Point::new(50.0, 0.0));
+        assert_eq!(line.point_at(125.0), Point::new(100.0, 25.0));
+        assert_eq!(line.point_at(-10.0), Point::new(0.0, 0.0));
+        assert_eq!(line.point_at(500.0), Point::new(100.0, 50.0));
+
+        assert_eq!(square.length(), 400.0);
+        assert_eq!(square.point_at(350.0), Point::new(0.0, 50.0));
+        assert_eq!(square.point_at(450.0), Point::new(50.0, 0.0));
+        assert_eq!(square.point_at(-50.0), Point::new(0.0, 50.0));
+
+        assert_eq!(line.point_at(f64::INFINITY), Point::new(100.0, 50.0));
+        assert_eq!(line.point_at(f64::NAN), Point::new(0.0, 0.0));
+        assert_eq!(square.point_at(f64::NAN), Point::new(0.0, 0.0));
+        assert_eq!(square.point_at(f64::INFINITY), Point::new(0.0, 0.0));
+        assert_eq!(square.tangent_at(f64::NAN), Point::new(0.0, 0.0));
+        assert_eq!(square.split_at(f64::NEG_INFINITY).0.points(), square.points());
+    }
+
+    #[test]
+    fn test_tangent_and_normal() {
+        let line = ell();
+        assert_eq!(line.tangent_at(10.0), Point::new(1.0, 0.0));
+        assert_eq!(line.tangent_at(120.0), Point::new(0.0, 1.0));
+        // exactly on a corner takes the outgoing segment.
+        assert_eq!(line.tangent_at(100.0), Point::new(0.0, 1.0));
+        let n = line.normal_at(10.0);
+        assert!(approx_eq(n.x, 0.0) && approx_eq(n.y, 1.0));
+    }
+
+    #[test]
+    fn test_resample() {
+        let square = Polyline::closed(Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec());
+        let line = ell();
+        let points = line.resample(4);
+        assert_eq!(points, vec![Point::new(0.0, 0.0), Point::new(50.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 50.0)]);
+        assert_eq!(square.resample(8).len(), 8);
+        assert_eq!(square.resample(8)[1], Point::new(50.0, 0.0));
+
+        let spaced = line.resample_spacing(40.0);
+        assert_eq!(spaced.len(), 5);
+        assert_eq!(spaced[3], Point::new(100.0, 20.0));
+        assert_eq!(spaced[4], Point::new(100.0, 50.0));
+        assert_eq!(square.resample_spacing(100.0).len(), 4);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let square = Polyline::closed(Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec());
+        let (a, b) = ell().split_at(120.0);
+        assert_eq!(a.points(), &[Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 20.0)]);
+        assert_eq!(b.points(), &[Point::new(100.0, 20.0), Point::new(100.0, 50.0)]);
+        assert!(approx_eq(a.length() + b.length(), 150.0));
+
+        let (a, b) = square.split_at(150.0);
+        assert!(!a.is_closed() && !b.is_closed());
+        assert_eq!(a.length(), 150.0);
+        assert_eq!(b.length(), 250.0);
+        assert_eq!(b.points()[b.points().len() - 1], Point::new(0.0, 0.0));
+    }
+}
@@ -0,0 +1,361 @@
+extern crate cairo;
+
+use self::cairo::Context;
+use canvas::BitContext;
+use super::point::Point;
+use super::rect::Rect;
+use super::circle::Circle;
+
+pub trait Spatial {
+    fn bounds(&self) -> Rect;
+
+    // zero if p is inside.
+    fn dist_to_point(&self, p: &Point) -> f64;
+
+    fn intersects_rect(&self, rect: &Rect) -> bool {
+        overlaps(&self.bounds(), rect)
+    }
+}
+
+impl Spatial for Point {
+    fn bounds(&self) -> Rect {
+        Rect::new(self.x, self.y, 0.0, 0.0)
+    }
+
+    fn dist_to_point(&self, p: &Point) -> f64 {
+        self.dist(p)
+    }
+}
+
+impl Spatial for Circle {
+    fn bounds(&self) -> Rect {
+        Circle::bounds(self)
+    }
+
+    fn dist_to_point(&self, p: &Point) -> f64 {
+        (p.dist(&self.center()) - self.r).max(0.0)
+    }
+
+    fn intersects_rect(&self, rect: &Rect) -> bool {
+        rect_dist(rect, &self.center()) <= self.r
+    }
+}
+
+impl Spatial for Rect {
+    fn bounds(&self) -> Rect {
+        *self
+    }
+
+    fn dist_to_point(&self, p: &Point) -> f64 {
+        rect_dist(self, p)
+    }
+}
+
+impl<U> Spatial for (Point, U) {
+    fn bounds(&self) -> Rect {
+        self.0.bounds()
+    }
+
+    fn dist_to_point(&self, p: &Point) -> f64 {
+        self.0.dist(p)
+    }
+}
+
+const MAX_DEPTH: i32 = 12;
+
+// items that straddle a split stay with the larger node. items outside the bounds are kept at the top, but slow every query down.
+pub struct Quadtree<T> {
+    root: Node<T>,
+    capacity: usize,
+    len: usize,
+}
+
+struct Node<T> {
+    rect: Rect,
+    depth: i32,
+    items: Vec<T>,
+    children: Vec<Node<T>>,
+}
+
+impl<T: Spatial> Quadtree<T> {
+    pub fn new(bounds: &Rect, capacity: usize) -> Quadtree<T> {
+        Quadtree {
+            root: Node::new(*bounds, 0),
+            capacity: capacity.max(1),
+            len: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> Rect {
+        self.root.rect
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, item: T) {
+        self.root.insert(item, self.capacity);
+        self.len += 1;
+    }
+
+    pub fn remove(&mut self, item: &T) -> Option<T>
+        where T: PartialEq
+    {
+        let removed = self.root.remove(item, self.capacity);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    // in no particular order.
+    pub fn items(&self) -> Vec<&T> {
+        let mut result = Vec::new();
+        self.root.collect(&mut |_| true, &mut |_| true, &mut result);
+        result
+    }
+
+    pub fn query_rect(&self, rect: &Rect) -> Vec<&T> {
+        let mut result = Vec::new();
+        self.root.collect(&mut |r| overlaps(r, rect), &mut |item| item.intersects_rect(rect), &mut result);
+        result
+    }
+
+    pub fn query_circle(&self, circle: &Circle) -> Vec<&T> {
+        let center = circle.center();
+        let mut result = Vec::new();
+        self.root.collect(&mut |r| rect_dist(r, &center) <= circle.r,
+                          &mut |item| item.dist_to_point(&center) <= circle.r,
+                          &mut result);
+        result
+    }
+
+    // nearest first.
+    pub fn nearest(&self, p: &Point, k: usize) -> Vec<&T> {
+        let mut best: Vec<(f64, &T)> = Vec::with_capacity(k + 1);
+        if k > 0 {
+            self.root.nearest(p, k, &mut best);
+        }
+        best.into_iter().map(|(_, item)| item).collect()
+    }
+
+    pub fn draw(&self, context: &Context) {
+        self.root.draw(context);
+    }
+}
+
+impl<T: Spatial> Node<T> {
+    fn new(rect: Rect, depth: i32) -> Node<T> {
+        Node { rect: rect, depth: depth, items: Vec::new(), children: Vec::new() }
+    }
+
+    // the child that wholly holds `bounds`, if any.
+    fn child_for(&self, bounds: &Rect) -> Option<usize> {
+        self.children.iter().position(|c| holds(&c.rect, bounds))
+    }
+
+    fn insert(&mut self, item: T, capacity: usize) {
+        if let Some(i) = self.child_for(&item.bounds()) {
+            self.children[i].insert(item, capacity);
+            return;
+        }
+        self.items.push(item);
+        if self.children.is_empty() && self.items.len() > capacity && self.depth < MAX_DEPTH {
+            self.split(capacity);
+        }
+    }
+
+    fn split(&mut self, capacity: usize) {
+        let depth = self.depth + 1;
+        self.children = self.rect.grid(2, 2).into_iter().map(|r| Node::new(r, depth)).collect();
+        let items: Vec<T> = self.items.drain(..).collect();
+        for item in items {
+            match self.child_for(&item.bounds()) {
+                Some(i) => self.children[i].insert(item, capacity),
+                None => self.items.push(item),
+            }
+        }
+    }
+
+    fn remove(&mut self, item: &T, capacity: usize) -> Option<T>
+        where T: PartialEq
+    {
+        let removed = match self.child_for(&item.bounds()) {
+            Some(i) => self.children[i].remove(item, capacity),
+            None => self.items.iter().position(|i| i == item).map(|i| self.items.remove(i)),
+        };
+        // fold the children back in once they've emptied out enough.
+        if removed.is_some() && !self.children.is_empty() && self.children.iter().all(|c| c.children.is_empty()) {
+            let total = self.items.len() + self.children.iter().fold(0, |sum, c| sum + c.items.len());
+            if total <= capacity {
+                for child in self.children.drain(..) {
+                    self.items.extend(child.items);
+                }
+            }
+        }
+        removed
+    }
+
+    fn collect<'a, N, I>(&'a self, node_test: &mut N, item_test: &mut I, result: &mut Vec<&'a T>)
+        where N: FnMut(&Rect) -> bool,
+              I: FnMut(&T) -> bool
+    {
+        // items at the top may lie outside its rect, so it's always searched.
+        if self.depth > 0 && !node_test(&self.rect) {
+            return;
+        }
+        result.extend(self.items.iter().filter(|item| item_test(item)));
+        for child in self.children.iter() {
+            child.collect(node_test, item_test, result);
+        }
+    }
+
+    fn nearest<'a>(&'a self, p: &Point, k: usize, best: &mut Vec<(f64, &'a T)>) {
+        for item in self.items.iter() {
+            let d = item.dist_to_point(p);
+            if best.len() < k || d < best[best.len() - 1].0 {
+                let at = best.iter().position(|&(bd, _)| bd > d).unwrap_or(best.len());
+                best.insert(at, (d, item));
+                best.truncate(k);
+            }
+        }
+        // nearest children first, so more of the rest can be skipped.
+        let mut order: Vec<(f64, &Node<T>)> = self.children.iter().map(|c| (rect_dist(&c.rect, p), c)).collect();
+        order.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        for (d, child) in order {
+            if best.len() == k && d >= best[best.len() - 1].0 {
+                break;
+            }
+            child.nearest(p, k, best);
+        }
+    }
+
+    fn draw(&self, context: &Context) {
+        context.stroke_rectangle(self.rect.x, self.rect.y, self.rect.w, self.rect.h);
+        for child in self.children.iter() {
+            child.draw(context);
+        }
+    }
+}
+
+// like Rect::intersects, but counting shared edges and zero sized rects.
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.left() <= b.right() && b.left() <= a.right() && a.top() <= b.bottom() && b.top() <= a.bottom()
+}
+
+fn holds(outer: &Rect, inner: &Rect) -> bool {
+    inner.left() >= outer.left() && inner.right() <= outer.right() &&
+        inner.top() >= outer.top() && inner.bottom() <= outer.bottom()
+}
+
+// distance from `p` to the nearest point of `rect`, zero inside.
+fn rect_dist(rect: &Rect, p: &Point) -> f64 {
+    let dx = (rect.left() - p.x).max(p.x - rect.right()).max(0.0);
+    let dy = (rect.top() - p.y).max(p.y - rect.bottom()).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Random;
+
+    fn bounds() -> Rect {
+        Rect::new(0.0, 0.0, 100.0, 100.0)
+    }
+
+    fn random_points(seed: usize, count: usize) -> Vec<Point> {
+        let mut rand = Random::from_seed(seed);
+        (0..count).map(|_| bounds().random_point(&mut rand)).collect()
+    }
+
+    fn sorted(mut points: Vec<Point>) -> Vec<Point> {
+        points.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap().then(a.y.partial_cmp(&b.y).unwrap()));
+        points
+    }
+
+    #[test]
+    fn test_queries() {
+        let points = random_points(1, 1000);
+        let mut tree = Quadtree::new(&bounds(), 4);
+        for p in points.iter() {
+            tree.insert(*p);
+        }
+        assert_eq!(tree.len(), 1000);
+        assert_eq!(tree.items().len(), 1000);
+
+        let rect = Rect::new(20.0, 30.0, 25.0, 10.0);
+        let found = sorted(tree.query_rect(&rect).into_iter().cloned().collect());
+        let expected = sorted(points.iter().filter(|p| rect.contains_point(p)).cloned().collect());
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+
+        let circle = Circle::new(60.0, 60.0, 12.0);
+        let found = sorted(tree.query_circle(&circle).into_iter().cloned().collect());
+        let expected = sorted(points.iter().filter(|p| circle.contains_point(p)).cloned().collect());
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = random_points(2, 500);
+        let mut tree = Quadtree::new(&bounds(), 8);
+        for (i, p) in points.iter().enumerate() {
+            tree.insert((*p, i));
+        }
+        let target = Point::new(33.0, 71.0);
+        let mut by_dist: Vec<usize> = (0..points.len()).collect();
+        by_dist.sort_by(|&a, &b| points[a].dist(&target).partial_cmp(&points[b].dist(&target)).unwrap());
+        let found: Vec<usize> = tree.nearest(&target, 5).iter().map(|&&(_, i)| i).collect();
+        assert_eq!(found, by_dist[..5].to_vec());
+        // asking for more than there are gives them all.
+        assert_eq!(tree.nearest(&target, 1000).len(), 500);
+        assert!(tree.nearest(&target, 0).is_empty());
+    }
+
+    #[test]
+    fn test_remove() {
+        let points = random_points(3, 200);
+        let mut tree = Quadtree::new(&bounds(), 4);
+        for p in points.iter() {
+            tree.insert(*p);
+        }
+        // including an item outside the bounds.
+        tree.insert(Point::new(-50.0, 10.0));
+        assert_eq!(tree.nearest(&Point::new(-40.0, 10.0), 1), vec![&Point::new(-50.0, 10.0)]);
+        assert_eq!(tree.remove(&Point::new(-50.0, 10.0)), Some(Point::new(-50.0, 10.0)));
+        for p in points[..150].iter() {
+            assert_eq!(tree.remove(p), Some(*p));
+        }
+        assert_eq!(tree.remove(&points[0]), None);
+        assert_eq!(tree.len(), 50);
+        assert_eq!(sorted(tree.items().into_iter().cloned().collect()), sorted(points[150..].to_vec()));
+    }
+
+    #[test]
+    fn test_circles() {
+        let mut rand = Random::from_seed(4);
+        let circles: Vec<Circle> = (0..300).map(|_| {
+            let p = bounds().random_point(&mut rand);
+            Circle::new(p.x, p.y, rand.float(0.5, 4.0))
+        }).collect();
+        let mut tree = Quadtree::new(&bounds(), 6);
+        for c in circles.iter() {
+            tree.insert(*c);
+        }
+        let probe = Circle::new(50.0, 50.0, 10.0);
+        let found = tree.query_circle(&probe).len();
+        let expected = circles.iter().filter(|c| c.center().dist(&probe.center()) <= c.r + probe.r).count();
+        assert_eq!(found, expected);
+        let rect = Rect::new(10.0, 10.0, 20.0, 20.0);
+        let expected = circles.iter().filter(|c| rect_dist(&rect, &c.center()) <= c.r).count();
+        assert_eq!(tree.query_rect(&rect).len(), expected);
+    }
+}
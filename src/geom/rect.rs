@@ -1,5 +1,8 @@
 use super::point::Point;
+use canvas::Canvas;
+use random::Random;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Rect {
     pub x: f64,
     pub y: f64,
@@ -8,7 +11,185 @@ pub struct Rect {
 }
 
 impl Rect {
-    pub fn contains_point(&self, p: Point) -> bool {
+    pub fn new(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect { x: x, y: y, w: w, h: h }
+    }
+
+    // empty at the origin if there are none.
+    pub fn from_points(points: &[Point]) -> Rect {
+        if points.is_empty() {
+            return Rect::new(0.0, 0.0, 0.0, 0.0);
+        }
+        let first = points[0];
+        let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+            (Point::new(min.x.min(p.x), min.y.min(p.y)), Point::new(max.x.max(p.x), max.y.max(p.y)))
+        });
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    pub fn from_canvas(canvas: &Canvas) -> Rect {
+        Rect::new(0.0, 0.0, canvas.width, canvas.height)
+    }
+
+    pub fn contains_point(&self, p: &Point) -> bool {
         p.x >= self.x && p.y >= self.y && p.x <= self.x + self.w && p.y <= self.y + self.h
     }
+
+    pub fn left(&self) -> f64 {
+        self.x
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.w
+    }
+
+    pub fn top(&self) -> f64 {
+        self.y
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.h
+    }
+
+    pub fn area(&self) -> f64 {
+        self.w * self.h
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        2.0 * (self.w + self.h)
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(self.x + self.w / 2.0, self.y + self.h / 2.0)
+    }
+
+    // clockwise on screen from the top left.
+    pub fn corners(&self) -> [Point; 4] {
+        [Point::new(self.left(), self.top()),
+         Point::new(self.right(), self.top()),
+         Point::new(self.right(), self.bottom()),
+         Point::new(self.left(), self.bottom())]
+    }
+
+    // negative amounts shrink it, but never past zero size.
+    pub fn inflate(&self, dx: f64, dy: f64) -> Rect {
+        let w = (self.w + dx * 2.0).max(0.0);
+        let h = (self.h + dy * 2.0).max(0.0);
+        let center = self.center();
+        Rect::new(center.x - w / 2.0, center.y - h / 2.0, w, h)
+    }
+
+    // rects that only share an edge don't intersect.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.left() < other.right() && other.left() < self.right() &&
+            self.top() < other.bottom() && other.top() < self.bottom()
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let x = self.left().max(other.left());
+        let y = self.top().max(other.top());
+        Some(Rect::new(x, y, self.right().min(other.right()) - x, self.bottom().min(other.bottom()) - y))
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.left().min(other.left());
+        let y = self.top().min(other.top());
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+
+    pub fn split_x(&self, fraction: f64) -> (Rect, Rect) {
+        let w = self.w * fraction;
+        (Rect::new(self.x, self.y, w, self.h), Rect::new(self.x + w, self.y, self.w - w, self.h))
+    }
+
+    pub fn split_y(&self, fraction: f64) -> (Rect, Rect) {
+        let h = self.h * fraction;
+        (Rect::new(self.x, self.y, self.w, h), Rect::new(self.x, self.y + h, self.w, self.h - h))
+    }
+
+    // in rows from the top left.
+    pub fn grid(&self, columns: i32, rows: i32) -> Vec<Rect> {
+        let mut cells = Vec::new();
+        if columns < 1 || rows < 1 {
+            return cells;
+        }
+        let w = self.w / columns as f64;
+        let h = self.h / rows as f64;
+        for row in 0..rows {
+            for column in 0..columns {
+                cells.push(Rect::new(self.x + w * column as f64, self.y + h * row as f64, w, h));
+            }
+        }
+        cells
+    }
+
+    pub fn random_point(&self, rand: &mut Random) -> Point {
+        Point::new(random_between(rand, self.left(), self.right()), random_between(rand, self.top(), self.bottom()))
+    }
+}
+
+// Random::float needs a non-empty range.
+fn random_between(rand: &mut Random, min: f64, max: f64) -> f64 {
+    if max > min { rand.float(min, max) } else { min }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect() -> Rect {
+        Rect::new(10.0, 20.0, 100.0, 50.0)
+    }
+
+    #[test]
+    fn test_measurements() {
+        let r = rect();
+        assert_eq!(r.area(), 5000.0);
+        assert_eq!(r.perimeter(), 300.0);
+        assert_eq!(r.center(), Point::new(60.0, 45.0));
+        assert_eq!(r.corners()[2], Point::new(110.0, 70.0));
+        assert!(r.contains_point(&Point::new(110.0, 70.0)));
+        assert!(!r.contains_point(&Point::new(111.0, 70.0)));
+        assert_eq!(Rect::from_points(&r.corners()), r);
+        assert_eq!(r.inflate(5.0, -10.0), Rect::new(5.0, 30.0, 110.0, 30.0));
+        assert_eq!(r.inflate(-100.0, 0.0).w, 0.0);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersection(&b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 15.0, 15.0));
+        let c = Rect::new(10.0, 0.0, 10.0, 10.0);
+        assert!(!a.intersects(&c));
+        assert_eq!(a.intersection(&c), None);
+    }
+
+    #[test]
+    fn test_split() {
+        let (left, right) = rect().split_x(0.25);
+        assert_eq!(left, Rect::new(10.0, 20.0, 25.0, 50.0));
+        assert_eq!(right, Rect::new(35.0, 20.0, 75.0, 50.0));
+        let (top, bottom) = rect().split_y(0.5);
+        assert_eq!(top.bottom(), bottom.top());
+        let cells = rect().grid(4, 2);
+        assert_eq!(cells.len(), 8);
+        assert_eq!(cells[5], Rect::new(35.0, 45.0, 25.0, 25.0));
+    }
+
+    #[test]
+    fn test_random_point() {
+        let mut rand = Random::from_seed(1);
+        let r = rect();
+        for _ in 0..100 {
+            assert!(r.contains_point(&r.random_point(&mut rand)));
+        }
+        assert_eq!(Rect::new(1.0, 2.0, 0.0, 0.0).random_point(&mut rand), Point::new(1.0, 2.0));
+    }
 }
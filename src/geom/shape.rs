@@ -0,0 +1,349 @@
+extern crate cairo;
+
+use self::cairo::Context;
+use canvas::BitContext;
+use math::TWO_PI;
+use super::point::Point;
+use super::rect::Rect;
+use super::circle::Circle;
+use super::ellipse::Ellipse;
+use super::polygon::Polygon;
+use super::bezier::{ CubicBezier, QuadBezier };
+use super::matrix::Matrix;
+
+// transforms may change the kind of shape: a rotated Rect becomes a Polygon, a stretched Circle an Ellipse.
+pub trait Shape {
+    fn contains(&self, p: &Point) -> bool;
+    fn bounds(&self) -> Rect;
+    fn area(&self) -> f64;
+
+    // curved outlines use resolution segments, straight ones just their corners.
+    fn to_path(&self, resolution: usize) -> Vec<Point>;
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape>;
+
+    fn draw(&self, context: &Context);
+
+    fn translate(&self, x: f64, y: f64) -> Box<dyn Shape> {
+        self.transform(&Matrix::from_translation(x, y))
+    }
+
+    // the way cairo's rotate turns.
+    fn rotate(&self, angle: f64, center: &Point) -> Box<dyn Shape> {
+        self.transform(&Matrix::from_translation(center.x, center.y).rotate(angle).translate(-center.x, -center.y))
+    }
+
+    fn scale(&self, sx: f64, sy: f64, center: &Point) -> Box<dyn Shape> {
+        self.transform(&Matrix::from_translation(center.x, center.y).scale(sx, sy).translate(-center.x, -center.y))
+    }
+
+    fn fill(&self, context: &Context) {
+        self.draw(context);
+        context.fill();
+    }
+
+    fn stroke(&self, context: &Context) {
+        self.draw(context);
+        context.stroke();
+    }
+}
+
+impl Shape for Rect {
+    fn contains(&self, p: &Point) -> bool {
+        self.contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        *self
+    }
+
+    fn area(&self) -> f64 {
+        Rect::area(self)
+    }
+
+    fn to_path(&self, _resolution: usize) -> Vec<Point> {
+        self.corners().to_vec()
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        let corners = matrix.transform_points(&self.corners());
+        // only turns and skews lose the right angles.
+        if matrix.yx == 0.0 && matrix.xy == 0.0 {
+            Box::new(Rect::from_points(&corners))
+        }
+        else {
+            Box::new(Polygon::new(corners))
+        }
+    }
+
+    fn draw(&self, context: &Context) {
+        context.rectangle(self.x, self.y, self.w, self.h);
+    }
+}
+
+impl Shape for Circle {
+    fn contains(&self, p: &Point) -> bool {
+        self.contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        Circle::bounds(self)
+    }
+
+    fn area(&self) -> f64 {
+        Circle::area(self)
+    }
+
+    fn to_path(&self, resolution: usize) -> Vec<Point> {
+        Ellipse::new(self.x, self.y, self.r, self.r, 0.0).to_path(resolution)
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        let (a, b, c, d) = (matrix.xx, matrix.yx, matrix.xy, matrix.yy);
+        let epsilon = 1e-12 * (a.abs() + b.abs() + c.abs() + d.abs());
+        // turning, mirroring and even scaling all keep it round.
+        let similar = ((a - d).abs() <= epsilon && (b + c).abs() <= epsilon) ||
+                      ((a + d).abs() <= epsilon && (b - c).abs() <= epsilon);
+        if similar {
+            let center = matrix.transform_point(&self.center());
+            Box::new(Circle::new(center.x, center.y, self.r * matrix.determinant().abs().sqrt()))
+        }
+        else {
+            Ellipse::new(self.x, self.y, self.r, self.r, 0.0).transform(matrix)
+        }
+    }
+
+    fn draw(&self, context: &Context) {
+        context.new_sub_path();
+        context.circle(self.x, self.y, self.r);
+    }
+}
+
+impl Shape for Ellipse {
+    fn contains(&self, p: &Point) -> bool {
+        self.contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        Ellipse::bounds(self)
+    }
+
+    fn area(&self) -> f64 {
+        Ellipse::area(self)
+    }
+
+    fn to_path(&self, resolution: usize) -> Vec<Point> {
+        let n = resolution.max(3);
+        (0..n).map(|i| self.point_at_angle(TWO_PI * i as f64 / n as f64)).collect()
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        // the outline is the unit circle under `l`. its singular values are the new radii and
+        // its left rotation the new angle, found with the closed form 2x2 SVD.
+        let (sin, cos) = self.rotation.sin_cos();
+        let (r11, r12, r21, r22) = (self.rx * cos, -self.ry * sin, self.rx * sin, self.ry * cos);
+        let l11 = matrix.xx * r11 + matrix.xy * r21;
+        let l12 = matrix.xx * r12 + matrix.xy * r22;
+        let l21 = matrix.yx * r11 + matrix.yy * r21;
+        let l22 = matrix.yx * r12 + matrix.yy * r22;
+        let (e, f) = ((l11 + l22) / 2.0, (l11 - l22) / 2.0);
+        let (g, h) = ((l21 + l12) / 2.0, (l21 - l12) / 2.0);
+        let (q, r) = ((e * e + h * h).sqrt(), (f * f + g * g).sqrt());
+        let rotation = (h.atan2(e) + g.atan2(f)) / 2.0;
+        let center = matrix.transform_point(&self.center());
+        Box::new(Ellipse::new(center.x, center.y, q + r, (q - r).abs(), rotation))
+    }
+
+    fn draw(&self, context: &Context) {
+        context.new_sub_path();
+        if self.rx == 0.0 || self.ry == 0.0 {
+            // a zero scale would break the context's matrix.
+            context.path(&self.to_path(4));
+            context.close_path();
+            return;
+        }
+        context.save();
+        context.translate(self.x, self.y);
+        context.rotate(self.rotation);
+        context.scale(self.rx, self.ry);
+        context.arc(0.0, 0.0, 1.0, 0.0, TWO_PI);
+        context.restore();
+    }
+}
+
+impl Shape for Polygon {
+    fn contains(&self, p: &Point) -> bool {
+        self.contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        Polygon::bounds(self)
+    }
+
+    fn area(&self) -> f64 {
+        Polygon::area(self)
+    }
+
+    fn to_path(&self, _resolution: usize) -> Vec<Point> {
+        self.points.clone()
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        Box::new(matrix.transform_polygon(self))
+    }
+
+    fn draw(&self, context: &Context) {
+        context.new_sub_path();
+        context.poly(self);
+    }
+}
+
+impl Shape for CubicBezier {
+    fn contains(&self, p: &Point) -> bool {
+        curve_outline(self).contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        CubicBezier::bounds(self)
+    }
+
+    fn area(&self) -> f64 {
+        curve_area(self)
+    }
+
+    fn to_path(&self, resolution: usize) -> Vec<Point> {
+        let n = resolution.max(1);
+        (0..=n).map(|i| self.point_at(i as f64 / n as f64)).collect()
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        Box::new(matrix.transform_bezier(self))
+    }
+
+    fn draw(&self, context: &Context) {
+        context.new_sub_path();
+        context.cubic_bezier(self);
+    }
+}
+
+impl Shape for QuadBezier {
+    fn contains(&self, p: &Point) -> bool {
+        curve_outline(&self.to_cubic()).contains_point(p)
+    }
+
+    fn bounds(&self) -> Rect {
+        QuadBezier::bounds(self)
+    }
+
+    fn area(&self) -> f64 {
+        curve_area(&self.to_cubic())
+    }
+
+    fn to_path(&self, resolution: usize) -> Vec<Point> {
+        self.to_cubic().to_path(resolution)
+    }
+
+    fn transform(&self, matrix: &Matrix) -> Box<dyn Shape> {
+        Box::new(QuadBezier::new(matrix.transform_point(&self.p0), matrix.transform_point(&self.p1),
+                                 matrix.transform_point(&self.p2)))
+    }
+
+    fn draw(&self, context: &Context) {
+        context.new_sub_path();
+        context.quad_bezier(self);
+    }
+}
+
+// the area between the curve and its chord, by Green's theorem. the integrand is a degree 5
+// polynomial, which three point Gauss-Legendre quadrature gets exactly.
+fn curve_area(curve: &CubicBezier) -> f64 {
+    let offset = 0.5 * (3.0f64 / 5.0).sqrt();
+    let nodes = [(0.5 - offset, 5.0 / 18.0), (0.5, 8.0 / 18.0), (0.5 + offset, 5.0 / 18.0)];
+    let along = nodes.iter().fold(0.0, |sum, &(t, weight)| sum + weight * curve.point_at(t).cross(&curve.derivative(t)));
+    ((along + curve.p3.cross(&curve.p0)) / 2.0).abs()
+}
+
+// the curve closed by its chord, flattened finely enough to test points against.
+fn curve_outline(curve: &CubicBezier) -> Polygon {
+    let bounds = CubicBezier::bounds(curve);
+    let tolerance = (bounds.w.max(bounds.h) * 1e-5).max(1e-9);
+    Polygon::new(curve.flatten(tolerance))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use math::PI;
+    use util::approx_eq;
+
+    fn shapes() -> Vec<Box<dyn Shape>> {
+        vec![Box::new(Rect::new(0.0, 0.0, 20.0, 10.0)),
+             Box::new(Circle::new(50.0, 50.0, 10.0)),
+             Box::new(Ellipse::new(100.0, 0.0, 20.0, 5.0, 0.0)),
+             Box::new(Polygon::new(vec![Point::new(0.0, 100.0), Point::new(30.0, 100.0), Point::new(0.0, 140.0)])),
+             Box::new(CubicBezier::new(Point::new(0.0, 0.0), Point::new(0.0, 40.0), Point::new(40.0, 40.0), Point::new(40.0, 0.0)))]
+    }
+
+    #[test]
+    fn test_common_interface() {
+        let shapes = shapes();
+        let areas = [200.0, PI * 100.0, PI * 100.0, 600.0, 960.0];
+        for (shape, &area) in shapes.iter().zip(areas.iter()) {
+            assert!((shape.area() - area).abs() < area * 1e-6, "{} != {}", shape.area(), area);
+            let bounds = shape.bounds();
+            for p in shape.to_path(32).iter() {
+                assert!(bounds.inflate(1e-9, 1e-9).contains_point(p));
+            }
+        }
+        assert!(shapes[1].contains(&Point::new(55.0, 55.0)));
+        assert!(!shapes[2].contains(&Point::new(100.0, 6.0)));
+        assert!(shapes[4].contains(&Point::new(20.0, 20.0)));
+        assert!(!shapes[4].contains(&Point::new(20.0, 31.0)));
+        assert_eq!(shapes[0].to_path(32).len(), 4);
+        assert_eq!(shapes[1].to_path(32).len(), 32);
+        assert_eq!(shapes[4].to_path(32).len(), 33);
+    }
+
+    #[test]
+    fn test_transforms_keep_area() {
+        let center = Point::new(10.0, 10.0);
+        for shape in shapes().iter() {
+            let area = shape.area();
+            let moved = shape.translate(5.0, -3.0);
+            assert!(approx_eq(moved.bounds().x, shape.bounds().x + 5.0));
+            assert!((shape.rotate(0.7, &center).area() - area).abs() < area * 1e-6);
+            assert!((shape.scale(2.0, 3.0, &center).area() - area * 6.0).abs() < area * 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_transformed_kinds() {
+        let rect = Rect::new(0.0, 0.0, 20.0, 10.0);
+        // a quarter turn about the center swaps the sides, but it's a polygon now.
+        let turned = rect.rotate(PI / 2.0, &rect.center());
+        assert_eq!(turned.to_path(0).len(), 4);
+        assert!(approx_eq(turned.bounds().w, 10.0) && approx_eq(turned.bounds().h, 20.0));
+        let scaled = rect.scale(-2.0, 1.0, &Point::new(0.0, 0.0));
+        assert!(approx_eq(scaled.bounds().x, -40.0) && approx_eq(scaled.area(), 400.0));
+
+        let circle = Circle::new(0.0, 0.0, 10.0);
+        assert_eq!(circle.rotate(1.0, &Point::new(10.0, 0.0)).to_path(8).len(), 8);
+        let stretched = circle.scale(2.0, 1.0, &Point::new(0.0, 0.0));
+        assert!(approx_eq(stretched.bounds().w, 40.0) && approx_eq(stretched.bounds().h, 20.0));
+    }
+
+    #[test]
+    fn test_ellipse_transform() {
+        // points on the transformed outline are the transformed points of the outline.
+        let ellipse = Ellipse::new(30.0, -10.0, 12.0, 4.0, 0.6);
+        let m = Matrix::identity().translate(5.0, 7.0).rotate(-0.4).skew(0.5).scale(1.5, 0.8);
+        let transformed = ellipse.transform(&m);
+        let outline = transformed.to_path(360);
+        for p in m.transform_points(&ellipse.to_path(40)).iter() {
+            let nearest = outline.iter().fold(f64::INFINITY, |min, q| min.min(q.dist(p)));
+            assert!(nearest < 0.2);
+        }
+        assert!((transformed.area() - ellipse.area() * m.determinant().abs()).abs() < 1e-6);
+    }
+}
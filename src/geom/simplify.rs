@@ -0,0 +1,246 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use super::point::Point;
+use super::dist_to_segment;
+
+// open paths keep both ends, closed paths at least the point furthest from the first.
+pub fn rdp(points: &[Point], tolerance: f64, closed: bool) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    if !closed {
+        return rdp_open(points, tolerance);
+    }
+    // split the loop at the first point and the point furthest from it, then simplify each half.
+    // NaN distances never count as furthest.
+    let mut far = 1;
+    let mut far_dist = -1.0;
+    for (i, p) in points.iter().enumerate().skip(1) {
+        let d = points[0].dist(p);
+        if d > far_dist {
+            far = i;
+            far_dist = d;
+        }
+    }
+    let mut second = points[far..].to_vec();
+    second.push(points[0]);
+    let mut result = rdp_open(&points[..far + 1], tolerance);
+    result.pop();
+    let mut rest = rdp_open(&second, tolerance);
+    rest.pop();
+    result.extend(rest);
+    result
+}
+
+fn rdp_open(points: &[Point], tolerance: f64) -> Vec<Point> {
+    let n = points.len();
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+    let mut stack = vec![(0, n - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut max_dist = 0.0;
+        let mut index = start;
+        for i in start + 1..end {
+            let d = dist_to_segment(&points[i], &points[start], &points[end]);
+            if d > max_dist {
+                max_dist = d;
+                index = i;
+            }
+        }
+        if max_dist > tolerance {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+    (0..n).filter(|&i| keep[i]).map(|i| points[i]).collect()
+}
+
+// drops the point making the smallest triangle with its neighbours until every triangle is at least min_area.
+pub fn visvalingam(points: &[Point], min_area: f64, closed: bool) -> Vec<Point> {
+    let n = points.len();
+    let min_points = if closed { 3 } else { 2 };
+    if n <= min_points {
+        return points.to_vec();
+    }
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+    let mut removed = vec![false; n];
+    let area = |prev: &[usize], next: &[usize], i: usize| {
+        if !closed && (i == 0 || i == n - 1) {
+            return f64::INFINITY;
+        }
+        triangle_area(&points[prev[i]], &points[i], &points[next[i]])
+    };
+
+    let mut areas: Vec<f64> = (0..n).map(|i| area(&prev, &next, i)).collect();
+    let mut heap: BinaryHeap<Candidate> = (0..n).map(|i| Candidate { area: areas[i], index: i }).collect();
+    let mut remaining = n;
+    // entries go stale when a neighbour is removed; those are skipped as they come up.
+    while let Some(Candidate { area: a, index: i }) = heap.pop() {
+        if removed[i] || a != areas[i] {
+            continue;
+        }
+        if a >= min_area || remaining <= min_points {
+            break;
+        }
+        removed[i] = true;
+        remaining -= 1;
+        let (p, q) = (prev[i], next[i]);
+        next[p] = q;
+        prev[q] = p;
+        for &j in [p, q].iter() {
+            // a neighbour's area never drops below that of the point just removed,
+            // so points are always taken in order.
+            areas[j] = area(&prev, &next, j).max(a);
+            heap.push(Candidate { area: areas[j], index: j });
+        }
+    }
+    (0..n).filter(|&i| !removed[i]).map(|i| points[i]).collect()
+}
+
+// min heap entry for visvalingam.
+#[derive(PartialEq)]
+struct Candidate {
+    area: f64,
+    index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other.area.partial_cmp(&self.area).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn triangle_area(p0: &Point, p1: &Point, p2: &Point) -> f64 {
+    (*p1 - *p0).cross(&(*p2 - *p0)).abs() / 2.0
+}
+
+// each pass cuts every corner, converging on a quadratic b-spline.
+pub fn chaikin(points: &[Point], iterations: i32, closed: bool) -> Vec<Point> {
+    let mut result = points.to_vec();
+    for _ in 0..iterations {
+        let n = result.len();
+        if n < 3 {
+            break;
+        }
+        let count = if closed { n } else { n - 1 };
+        let mut cut = Vec::with_capacity(count * 2 + 2);
+        if !closed {
+            cut.push(result[0]);
+        }
+        for i in 0..count {
+            let p0 = result[i];
+            let p1 = result[(i + 1) % n];
+            cut.push(p0.lerp(&p1, 0.25));
+            cut.push(p0.lerp(&p1, 0.75));
+        }
+        if !closed {
+            cut.push(result[n - 1]);
+        }
+        result = cut;
+    }
+    result
+}
+
+// shapes shrink a little with each pass.
+pub fn laplacian(points: &[Point], iterations: i32, strength: f64, closed: bool) -> Vec<Point> {
+    let mut result = points.to_vec();
+    let n = result.len();
+    if n < 3 {
+        return result;
+    }
+    for _ in 0..iterations {
+        let current = result.clone();
+        for i in 0..n {
+            if !closed && (i == 0 || i == n - 1) {
+                continue;
+            }
+            let mid = current[(i + n - 1) % n].lerp(&current[(i + 1) % n], 0.5);
+            result[i] = current[i].lerp(&mid, strength);
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use geom::polygon_points;
+    use geom::polygon::Polygon;
+    use geom::polyline::Polyline;
+    use geom::rect::Rect;
+
+    fn zigzag() -> Vec<Point> {
+        vec![Point::new(0.0, 0.0), Point::new(10.0, 6.5), Point::new(20.0, 14.0), Point::new(30.0, 20.0),
+             Point::new(40.0, 10.5), Point::new(50.0, 0.0)]
+    }
+
+    fn dotted_square() -> Vec<Point> {
+        // a square with extra points halfway along its edges.
+        Polyline::closed(Rect::new(0.0, 0.0, 100.0, 100.0).corners().to_vec()).resample(8)
+    }
+
+    #[test]
+    fn test_rdp() {
+        let simple = rdp(&zigzag(), 1.0, false);
+        assert_eq!(simple, vec![Point::new(0.0, 0.0), Point::new(30.0, 20.0), Point::new(50.0, 0.0)]);
+        assert_eq!(rdp(&zigzag(), 0.1, false).len(), 6);
+        let closed = rdp(&dotted_square(), 1.0, true);
+        assert_eq!(closed.len(), 4);
+        assert!(approx_eq(Polygon::new(closed).area(), 10000.0));
+
+        let mut points = dotted_square();
+        points.insert(2, Point::new(f64::NAN, 0.0));
+        assert!(rdp(&points, 1.0, true).contains(&Point::new(100.0, 100.0)));
+    }
+
+    #[test]
+    fn test_visvalingam() {
+        let simple = visvalingam(&zigzag(), 20.0, false);
+        assert_eq!(simple, vec![Point::new(0.0, 0.0), Point::new(30.0, 20.0), Point::new(50.0, 0.0)]);
+        let closed = visvalingam(&dotted_square(), 1.0, true);
+        assert_eq!(closed.len(), 4);
+        assert!(approx_eq(Polygon::new(closed).area(), 10000.0));
+        // closed paths never drop below a triangle.
+        assert_eq!(visvalingam(&dotted_square(), 1e9, true).len(), 3);
+    }
+
+    #[test]
+    fn test_chaikin() {
+        let open = chaikin(&zigzag(), 2, false);
+        assert_eq!(open.len(), 2 + 2 * (2 + 2 * 5 - 1));
+        assert_eq!(open[0], Point::new(0.0, 0.0));
+        assert_eq!(open[open.len() - 1], Point::new(50.0, 0.0));
+        let closed = chaikin(&dotted_square(), 3, true);
+        assert_eq!(closed.len(), 8 * 8);
+        let area = Polygon::new(closed).area();
+        assert!(area < 10000.0 && area > 9000.0);
+    }
+
+    #[test]
+    fn test_laplacian() {
+        let open = laplacian(&zigzag(), 5, 0.5, false);
+        assert_eq!(open[0], Point::new(0.0, 0.0));
+        assert_eq!(open[5], Point::new(50.0, 0.0));
+        assert!(open[3].y < 20.0);
+
+        // a regular polygon keeps its center and shape but shrinks.
+        let hexagon = polygon_points(10.0, 20.0, 50.0, 6, 0.0);
+        let smoothed = Polygon::new(laplacian(&hexagon, 3, 0.5, true));
+        let c = smoothed.centroid();
+        assert!(approx_eq(c.x, 10.0) && approx_eq(c.y, 20.0));
+        assert!(smoothed.area() < Polygon::new(hexagon).area());
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use super::point::Point;
+
+// points are referred to by insertion order. cells about the size of the query radius work best.
+pub struct SpatialHash {
+    cell_size: f64,
+    points: Vec<Point>,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SpatialHash {
+    // panics unless cell_size is positive.
+    pub fn new(cell_size: f64) -> SpatialHash {
+        assert!(cell_size > 0.0, "cell size must be positive");
+        SpatialHash {
+            cell_size: cell_size,
+            points: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn from_points(points: &[Point], cell_size: f64) -> SpatialHash {
+        let mut hash = SpatialHash::new(cell_size);
+        for p in points.iter() {
+            hash.insert(*p);
+        }
+        hash
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, i: usize) -> Point {
+        self.points[i]
+    }
+
+    pub fn insert(&mut self, p: Point) -> usize {
+        let i = self.points.len();
+        let cell = self.cell(&p);
+        self.cells.entry(cell).or_default().push(i);
+        self.points.push(p);
+        i
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.cells.clear();
+    }
+
+    // including any at p itself.
+    pub fn neighbors(&self, p: &Point, radius: f64) -> Vec<usize> {
+        let mut result = Vec::new();
+        let (x0, y0) = self.cell(&Point::new(p.x - radius, p.y - radius));
+        let (x1, y1) = self.cell(&Point::new(p.x + radius, p.y + radius));
+        // with more cells in range than are filled, it's quicker to check every point.
+        if (x1 as f64 - x0 as f64 + 1.0) * (y1 as f64 - y0 as f64 + 1.0) > self.cells.len() as f64 {
+            result.extend((0..self.points.len()).filter(|&i| self.points[i].dist(p) <= radius));
+            return result;
+        }
+        for cx in x0..=x1 {
+            for cy in y0..=y1 {
+                if let Some(bucket) = self.cells.get(&(cx, cy)) {
+                    result.extend(bucket.iter().cloned().filter(|&i| self.points[i].dist(p) <= radius));
+                }
+            }
+        }
+        result
+    }
+
+    // lower index first.
+    pub fn pairs(&self, radius: f64) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        for (i, p) in self.points.iter().enumerate() {
+            result.extend(self.neighbors(p, radius).into_iter().filter(|&j| j > i).map(|j| (i, j)));
+        }
+        result
+    }
+
+    fn cell(&self, p: &Point) -> (i64, i64) {
+        ((p.x / self.cell_size).floor() as i64, (p.y / self.cell_size).floor() as i64)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use random::Random;
+
+    fn random_points(seed: usize, count: usize) -> Vec<Point> {
+        let mut rand = Random::from_seed(seed);
+        (0..count).map(|_| Point::new(rand.float(-50.0, 150.0), rand.float(0.0, 100.0))).collect()
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let points = random_points(1, 800);
+        let hash = SpatialHash::from_points(&points, 10.0);
+        assert_eq!(hash.len(), 800);
+        for &(p, radius) in [(Point::new(20.0, 30.0), 8.0), (Point::new(-45.0, 95.0), 25.0), (points[5], 3.0)].iter() {
+            let mut found = hash.neighbors(&p, radius);
+            found.sort();
+            let expected: Vec<usize> = (0..points.len()).filter(|&i| points[i].dist(&p) <= radius).collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_pairs() {
+        let points = random_points(2, 300);
+        let hash = SpatialHash::from_points(&points, 5.0);
+        let mut found = hash.pairs(5.0);
+        found.sort();
+        let mut expected = Vec::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                if points[i].dist(&points[j]) <= 5.0 {
+                    expected.push((i, j));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut hash = SpatialHash::new(4.0);
+        assert_eq!(hash.insert(Point::new(1.0, 1.0)), 0);
+        assert_eq!(hash.insert(Point::new(2.0, 2.0)), 1);
+        assert_eq!(hash.neighbors(&Point::new(0.0, 0.0), 2.0), vec![0]);
+        hash.clear();
+        assert!(hash.is_empty());
+        assert!(hash.neighbors(&Point::new(0.0, 0.0), 10.0).is_empty());
+
+        // a radius spanning far more cells than are filled still answers straight away.
+        let hash = SpatialHash::from_points(&random_points(3, 50), 0.001);
+        assert_eq!(hash.neighbors(&Point::new(0.0, 0.0), 1e9).len(), 50);
+        assert_eq!(hash.neighbors(&Point::new(0.0, 0.0), f64::INFINITY).len(), 50);
+    }
+}
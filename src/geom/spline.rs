@@ -0,0 +1,225 @@
+use super::point::Point;
+use super::{ bezier_point, quadratic_point, dist };
+
+// centripetal avoids the cusps and self-intersections of uniform splines.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CatmullRom {
+    Uniform,
+    Centripetal,
+    Chordal,
+}
+
+impl CatmullRom {
+    pub fn alpha(&self) -> f64 {
+        match *self {
+            CatmullRom::Uniform => 0.0,
+            CatmullRom::Centripetal => 0.5,
+            CatmullRom::Chordal => 1.0,
+        }
+    }
+}
+
+// each segment is [start, control0, control1, end].
+pub fn catmull_rom_beziers(points: &[Point], kind: CatmullRom, closed: bool) -> Vec<[Point; 4]> {
+    let mut result = Vec::new();
+    let n = points.len();
+    if n < 2 {
+        return result;
+    }
+    let alpha = kind.alpha();
+    let count = if closed { n } else { n - 1 };
+    for i in 0..count {
+        let p1 = points[i];
+        let p2 = points[(i + 1) % n];
+        // open ends get a phantom neighbour mirrored through the end point.
+        let p0 = if i > 0 || closed { points[(i + n - 1) % n] } else { p1 * 2.0 - p2 };
+        let p3 = if i + 2 < n || closed { points[(i + 2) % n] } else { p2 * 2.0 - p1 };
+        result.push(catmull_rom_segment(p0, p1, p2, p3, alpha));
+    }
+    result
+}
+
+// open splines repeat their end points so the curve starts and ends on them.
+pub fn bspline_beziers(points: &[Point], closed: bool) -> Vec<[Point; 4]> {
+    let mut result = Vec::new();
+    let n = points.len();
+    if n < 2 {
+        return result;
+    }
+    let mut control: Vec<Point> = Vec::new();
+    if closed {
+        for i in 0..n + 3 {
+            control.push(points[i % n]);
+        }
+    }
+    else {
+        control.push(points[0]);
+        control.push(points[0]);
+        control.extend(points.iter());
+        control.push(points[n - 1]);
+        control.push(points[n - 1]);
+    }
+    for w in control.windows(4) {
+        let (p0, p1, p2, p3) = (w[0], w[1], w[2], w[3]);
+        result.push([
+            (p0 + p1 * 4.0 + p2) / 6.0,
+            (p1 * 2.0 + p2) / 3.0,
+            (p1 + p2 * 2.0) / 3.0,
+            (p1 + p2 * 4.0 + p3) / 6.0,
+        ]);
+    }
+    result
+}
+
+pub fn catmull_rom_points(points: &[Point], kind: CatmullRom, closed: bool, segments: i32) -> Vec<Point> {
+    sample_beziers(&catmull_rom_beziers(points, kind, closed), segments)
+}
+
+pub fn bspline_points(points: &[Point], closed: bool, segments: i32) -> Vec<Point> {
+    sample_beziers(&bspline_beziers(points, closed), segments)
+}
+
+// the path drawn by BitContext::multi_curve.
+pub fn multi_curve_points(points: &[Point], segments: i32) -> Vec<Point> {
+    let n = points.len();
+    if n < 3 {
+        return points.to_vec();
+    }
+    let segments = segments.max(1);
+    let mut result = vec![points[0]];
+    let mut start = points[0].lerp(&points[1], 0.5);
+    result.push(start);
+    for i in 1..n - 1 {
+        let end = points[i].lerp(&points[i + 1], 0.5);
+        for j in 1..segments + 1 {
+            result.push(quadratic_point(start, points[i], end, j as f64 / segments as f64));
+        }
+        start = end;
+    }
+    result.push(points[n - 1]);
+    result
+}
+
+// the closed path drawn by BitContext::multi_loop. the first point is not repeated.
+pub fn multi_loop_points(points: &[Point], segments: i32) -> Vec<Point> {
+    let n = points.len();
+    if n < 3 {
+        return points.to_vec();
+    }
+    let segments = segments.max(1);
+    let mut result = Vec::new();
+    for i in 0..n {
+        let start = points[(i + n - 1) % n].lerp(&points[i], 0.5);
+        let end = points[i].lerp(&points[(i + 1) % n], 0.5);
+        for j in 0..segments {
+            result.push(quadratic_point(start, points[i], end, j as f64 / segments as f64));
+        }
+    }
+    result
+}
+
+fn sample_beziers(beziers: &[[Point; 4]], segments: i32) -> Vec<Point> {
+    let mut result = Vec::new();
+    let segments = segments.max(1);
+    for (i, b) in beziers.iter().enumerate() {
+        // skip the first point of each later span, it duplicates the last one.
+        let start = if i == 0 { 0 } else { 1 };
+        for j in start..segments + 1 {
+            let t = j as f64 / segments as f64;
+            result.push(bezier_point(b[0], b[1], b[2], b[3], t));
+        }
+    }
+    result
+}
+
+fn catmull_rom_segment(p0: Point, p1: Point, p2: Point, p3: Point, alpha: f64) -> [Point; 4] {
+    let d1 = dist(&p0, &p1).powf(alpha);
+    let d2 = dist(&p1, &p2).powf(alpha);
+    let d3 = dist(&p2, &p3).powf(alpha);
+
+    // control points from Yuksel et al., falling back to the end points for coincident knots.
+    let c0 = if d1 * (d1 + d2) == 0.0 {
+        p1
+    }
+    else {
+        let a = d1 * d1;
+        let b = d2 * d2;
+        (p2 * a - p0 * b + p1 * (2.0 * a + 3.0 * d1 * d2 + b)) / (3.0 * d1 * (d1 + d2))
+    };
+    let c1 = if d3 * (d3 + d2) == 0.0 {
+        p2
+    }
+    else {
+        let a = d3 * d3;
+        let b = d2 * d2;
+        (p1 * a - p3 * b + p2 * (2.0 * a + 3.0 * d3 * d2 + b)) / (3.0 * d3 * (d3 + d2))
+    };
+    [p1, c0, c1, p2]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use geom::rect::Rect;
+
+    #[test]
+    fn test_catmull_rom_passes_through_points() {
+        let points = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        for kind in [CatmullRom::Uniform, CatmullRom::Centripetal, CatmullRom::Chordal].iter() {
+            let open = catmull_rom_points(&points, *kind, false, 8);
+            assert_eq!(open.len(), 3 * 8 + 1);
+            for (i, p) in points.iter().enumerate() {
+                assert_eq!(&open[i * 8], p);
+            }
+            let closed = catmull_rom_points(&points, *kind, true, 8);
+            assert_eq!(closed.len(), 4 * 8 + 1);
+            assert_eq!(closed[0], closed[closed.len() - 1]);
+        }
+    }
+
+    #[test]
+    fn test_multi_curve() {
+        let points = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        let open = multi_curve_points(&points, 4);
+        assert_eq!(open.len(), 2 + 2 * 4 + 1);
+        assert_eq!(open[0], points[0]);
+        assert_eq!(open[1], Point::new(50.0, 0.0));
+        assert_eq!(open[open.len() - 1], points[3]);
+        let closed = multi_loop_points(&points, 4);
+        assert_eq!(closed.len(), 4 * 4);
+        assert_eq!(closed[0], Point::new(0.0, 50.0));
+        assert_eq!(closed[4], Point::new(50.0, 0.0));
+    }
+
+    #[test]
+    fn test_uniform_tangent() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        // uniform catmull-rom tangents are (p2 - p0) / 2, so control points sit a sixth of that away.
+        let b = catmull_rom_beziers(&square, CatmullRom::Uniform, true);
+        assert!(approx_eq(b[1][1].x, 100.0 + 100.0 / 6.0));
+        assert!(approx_eq(b[1][1].y, 100.0 / 6.0));
+    }
+
+    #[test]
+    fn test_bspline() {
+        let points = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        let open = bspline_points(&points, false, 4);
+        assert_eq!(open[0], points[0]);
+        assert_eq!(open[open.len() - 1], points[3]);
+
+        let closed = bspline_beziers(&points, true);
+        assert_eq!(closed.len(), 4);
+        // a closed uniform b-spline of a square starts at (p0 + 4 * p1 + p2) / 6.
+        assert!(approx_eq(closed[0][0].x, 500.0 / 6.0));
+        assert!(approx_eq(closed[0][0].y, 100.0 / 6.0));
+        assert_eq!(closed[3][3], closed[0][0]);
+    }
+
+    #[test]
+    fn test_too_few_points() {
+        assert!(catmull_rom_points(&[Point::new(1.0, 1.0)], CatmullRom::Centripetal, false, 8).is_empty());
+        assert!(bspline_points(&[], true, 8).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use super::rect::Rect;
+use random::Random;
+
+// every part is split down to min_depth, then with chance split_chance until max_depth, never below min_size.
+pub struct Subdivision {
+    pub min_depth: i32,
+    pub max_depth: i32,
+    pub min_size: f64,
+    pub min_ratio: f64,
+    pub max_ratio: f64,
+    pub gutter: f64,
+    pub split_chance: f64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Node {
+    pub rect: Rect,
+    pub depth: i32,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    // depth first order.
+    pub fn leaves(&self) -> Vec<Rect> {
+        let mut result = Vec::new();
+        self.collect_leaves(&mut result);
+        result
+    }
+
+    fn collect_leaves(&self, result: &mut Vec<Rect>) {
+        if self.is_leaf() {
+            result.push(self.rect);
+        }
+        for child in self.children.iter() {
+            child.collect_leaves(result);
+        }
+    }
+}
+
+impl Subdivision {
+    pub fn new(max_depth: i32, min_size: f64) -> Subdivision {
+        Subdivision {
+            min_depth: 1,
+            max_depth: max_depth,
+            min_size: min_size,
+            min_ratio: 0.25,
+            max_ratio: 0.75,
+            gutter: 0.0,
+            split_chance: 0.7,
+        }
+    }
+
+    pub fn tree(&self, rect: &Rect, rand: &mut Random) -> Node {
+        self.split(rect, 0, rand)
+    }
+
+    pub fn leaves(&self, rect: &Rect, rand: &mut Random) -> Vec<Rect> {
+        self.tree(rect, rand).leaves()
+    }
+
+    fn split(&self, rect: &Rect, depth: i32, rand: &mut Random) -> Node {
+        let mut node = Node { rect: *rect, depth: depth, children: Vec::new() };
+        if depth >= self.max_depth || rect.w + rect.h <= 0.0 ||
+           (depth >= self.min_depth && !rand.weighted_bool(self.split_chance)) {
+            return node;
+        }
+        let ratio = if self.max_ratio > self.min_ratio { rand.float(self.min_ratio, self.max_ratio) } else { self.min_ratio };
+        let across = rand.float(0.0, rect.w + rect.h) < rect.w;
+        // fall back to the other direction if the first choice would leave a part too small.
+        let parts = self.cut(rect, across, ratio).or_else(|| self.cut(rect, !across, ratio));
+        if let Some((a, b)) = parts {
+            node.children.push(self.split(&a, depth + 1, rand));
+            node.children.push(self.split(&b, depth + 1, rand));
+        }
+        node
+    }
+
+    // cuts the width if `across` is set, else the height, keeping both parts at least `min_size`.
+    fn cut(&self, rect: &Rect, across: bool, ratio: f64) -> Option<(Rect, Rect)> {
+        let length = if across { rect.w } else { rect.h };
+        let available = length - self.gutter;
+        let min = self.min_size.max(0.0);
+        if available < min * 2.0 || available <= 0.0 {
+            return None;
+        }
+        let first = (available * ratio).max(min).min(available - min);
+        let second = available - first;
+        if across {
+            Some((Rect::new(rect.x, rect.y, first, rect.h),
+                  Rect::new(rect.x + first + self.gutter, rect.y, second, rect.h)))
+        }
+        else {
+            Some((Rect::new(rect.x, rect.y, rect.w, first),
+                  Rect::new(rect.x, rect.y + first + self.gutter, rect.w, second)))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    fn page() -> Rect {
+        Rect::new(0.0, 0.0, 400.0, 300.0)
+    }
+
+    #[test]
+    fn test_leaves_cover_area() {
+        let mut rand = Random::from_seed(7);
+        let mut s = Subdivision::new(6, 10.0);
+        let leaves = s.leaves(&page(), &mut rand);
+        assert!(leaves.len() > 1);
+        let area = leaves.iter().fold(0.0, |sum, r| sum + r.area());
+        assert!(approx_eq(area, page().area()));
+        for r in leaves.iter() {
+            assert!(r.w >= 10.0 - 1e-9 && r.h >= 10.0 - 1e-9);
+        }
+
+        // gutters take their share out of the area.
+        s.gutter = 4.0;
+        let tree = s.tree(&page(), &mut rand);
+        let leaves = tree.leaves();
+        let area = leaves.iter().fold(0.0, |sum, r| sum + r.area());
+        assert!(area < page().area());
+        for (i, a) in leaves.iter().enumerate() {
+            for b in leaves[i + 1..].iter() {
+                assert!(a.intersection(b).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_depth_limits() {
+        let mut rand = Random::from_seed(3);
+        let mut s = Subdivision::new(3, 1.0);
+        s.min_depth = 3;
+        // splitting to a fixed depth always gives 2^depth leaves.
+        assert_eq!(s.leaves(&page(), &mut rand).len(), 8);
+        s.max_depth = 0;
+        assert_eq!(s.leaves(&page(), &mut rand), vec![page()]);
+        // nothing can be split below twice the minimum size.
+        let s = Subdivision::new(10, 250.0);
+        assert_eq!(s.tree(&page(), &mut rand).leaves().len(), 1);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let s = Subdivision::new(8, 5.0);
+        let a = s.leaves(&page(), &mut Random::from_seed(11));
+        let b = s.leaves(&page(), &mut Random::from_seed(11));
+        assert_eq!(a, b);
+    }
+}
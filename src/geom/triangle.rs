@@ -0,0 +1,102 @@
+use super::point::Point;
+use super::circle::Circle;
+use super::polygon::Polygon;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    pub a: Point,
+    pub b: Point,
+    pub c: Point,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point) -> Triangle {
+        Triangle { a: a, b: b, c: c }
+    }
+
+    pub fn points(&self) -> [Point; 3] {
+        [self.a, self.b, self.c]
+    }
+
+    // positive when clockwise on screen, as for Polygon.
+    pub fn signed_area(&self) -> f64 {
+        (self.b - self.a).cross(&(self.c - self.a)) / 2.0
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.a.dist(&self.b) + self.b.dist(&self.c) + self.c.dist(&self.a)
+    }
+
+    pub fn centroid(&self) -> Point {
+        (self.a + self.b + self.c) / 3.0
+    }
+
+    pub fn circumcircle(&self) -> Option<Circle> {
+        Circle::through_points(&self.a, &self.b, &self.c)
+    }
+
+    // a flat triangle gives a circle of zero radius.
+    pub fn incircle(&self) -> Circle {
+        // the center is the corners weighted by the lengths of the opposite sides.
+        let la = self.b.dist(&self.c);
+        let lb = self.c.dist(&self.a);
+        let lc = self.a.dist(&self.b);
+        let perimeter = la + lb + lc;
+        if perimeter == 0.0 {
+            return Circle::new(self.a.x, self.a.y, 0.0);
+        }
+        let center = (self.a * la + self.b * lb + self.c * lc) / perimeter;
+        Circle::new(center.x, center.y, 2.0 * self.area() / perimeter)
+    }
+
+    // inside or on the edges, whichever way the corners run.
+    pub fn contains_point(&self, p: &Point) -> bool {
+        let d0 = (self.b - self.a).cross(&(*p - self.a));
+        let d1 = (self.c - self.b).cross(&(*p - self.b));
+        let d2 = (self.a - self.c).cross(&(*p - self.c));
+        !((d0 < 0.0 || d1 < 0.0 || d2 < 0.0) && (d0 > 0.0 || d1 > 0.0 || d2 > 0.0))
+    }
+
+    pub fn to_polygon(&self) -> Polygon {
+        Polygon::new(vec![self.a, self.b, self.c])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    #[test]
+    fn test_measurements() {
+        // a 3, 4, 5 right angled triangle.
+        let t = Triangle::new(Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 3.0));
+        assert_eq!(t.area(), 6.0);
+        assert!(t.signed_area() > 0.0);
+        assert_eq!(t.perimeter(), 12.0);
+        assert!(approx_eq(t.centroid().x, 4.0 / 3.0) && approx_eq(t.centroid().y, 1.0));
+        assert!(t.contains_point(&Point::new(1.0, 1.0)));
+        assert!(t.contains_point(&Point::new(2.0, 0.0)));
+        assert!(!t.contains_point(&Point::new(3.0, 3.0)));
+        assert_eq!(t.to_polygon().signed_area(), t.signed_area());
+    }
+
+    #[test]
+    fn test_circles() {
+        let t = Triangle::new(Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(0.0, 3.0));
+        // the circumcircle of a right angled triangle sits on the hypotenuse.
+        let circum = t.circumcircle().expect("no circumcircle");
+        assert!(approx_eq(circum.x, 2.0) && approx_eq(circum.y, 1.5) && approx_eq(circum.r, 2.5));
+        let incircle = t.incircle();
+        assert!(approx_eq(incircle.x, 1.0) && approx_eq(incircle.y, 1.0) && approx_eq(incircle.r, 1.0));
+
+        let flat = Triangle::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 2.0));
+        assert_eq!(flat.circumcircle(), None);
+        assert_eq!(flat.incircle().r, 0.0);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{ BTreeMap, HashSet, VecDeque };
+use super::point::Point;
+use super::polygon::Polygon;
+use super::delaunay::in_circumcircle;
+
+// ear clipping. triangles are indices into polygon.points, clockwise on screen.
+pub fn triangulate(polygon: &Polygon) -> Vec<[usize; 3]> {
+    triangulate_with_holes(polygon, &[])
+}
+
+// holes must lie inside the outline without touching each other. indices are as in flatten_holes.
+pub fn triangulate_with_holes(outline: &Polygon, holes: &[Polygon]) -> Vec<[usize; 3]> {
+    let points = flatten_holes(outline, holes);
+    let ring = bridge_holes(&points, outline, holes);
+    clip_ears(&points, ring)
+}
+
+// constrained delaunay: inner edges are flipped, outline and hole edges kept.
+pub fn triangulate_constrained(outline: &Polygon, holes: &[Polygon]) -> Vec<[usize; 3]> {
+    let points = flatten_holes(outline, holes);
+    let mut triangles = triangulate_with_holes(outline, holes);
+    let mut fixed = HashSet::new();
+    let mut start = 0;
+    for ring in Some(outline).into_iter().chain(holes.iter()) {
+        let n = ring.len();
+        for i in 0..n {
+            let (a, b) = (start + i, start + (i + 1) % n);
+            fixed.insert((a.min(b), a.max(b)));
+        }
+        start += n;
+    }
+    flip_edges(&points, &mut triangles, &fixed);
+    triangles
+}
+
+pub fn flatten_holes(outline: &Polygon, holes: &[Polygon]) -> Vec<Point> {
+    let mut points = outline.points.clone();
+    for hole in holes.iter() {
+        points.extend(hole.points.iter().cloned());
+    }
+    points
+}
+
+// a single clockwise ring of indices, with each hole cut into the outline along a bridge
+// from its rightmost point, so the bridge points appear twice.
+fn bridge_holes(points: &[Point], outline: &Polygon, holes: &[Polygon]) -> Vec<usize> {
+    let mut ring: Vec<usize> = (0..outline.len()).collect();
+    if outline.signed_area() < 0.0 {
+        ring.reverse();
+    }
+    let mut hole_rings = Vec::new();
+    let mut start = outline.len();
+    for hole in holes.iter() {
+        let mut hole_ring: Vec<usize> = (start..start + hole.len()).collect();
+        // holes run the other way to the outline.
+        if hole.signed_area() > 0.0 {
+            hole_ring.reverse();
+        }
+        start += hole.len();
+        if hole_ring.len() >= 3 {
+            hole_rings.push(hole_ring);
+        }
+    }
+    // bridging the rightmost holes first keeps later bridges from crossing them.
+    let rightmost = |hole: &Vec<usize>| {
+        (0..hole.len()).max_by(|&a, &b| points[hole[a]].x.partial_cmp(&points[hole[b]].x).unwrap()).unwrap()
+    };
+    hole_rings.sort_by(|a, b| points[b[rightmost(b)]].x.partial_cmp(&points[a[rightmost(a)]].x).unwrap());
+    for hole in hole_rings.iter() {
+        let m = rightmost(hole);
+        if let Some(at) = find_bridge(points, &ring, &points[hole[m]]) {
+            let mut cut: Vec<usize> = (0..=hole.len()).map(|k| hole[(m + k) % hole.len()]).collect();
+            cut.push(ring[at]);
+            let tail = ring.split_off(at + 1);
+            ring.extend(cut);
+            ring.extend(tail);
+        }
+    }
+    ring
+}
+
+// the position in `ring` of a point that can be seen from `m`, found by casting a ray to the right.
+fn find_bridge(points: &[Point], ring: &[usize], m: &Point) -> Option<usize> {
+    let n = ring.len();
+    let mut best: Option<(f64, usize)> = None;
+    for i in 0..n {
+        let (a, b) = (points[ring[i]], points[ring[(i + 1) % n]]);
+        if (a.y > m.y) == (b.y > m.y) || a.y == b.y {
+            continue;
+        }
+        let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+        if x < m.x || best.map_or(false, |(bx, _)| x >= bx) {
+            continue;
+        }
+        // the end of the edge furthest right, unless the ray lands right on a point.
+        let at = if x == a.x && a.y == m.y { i } else if b.x > a.x { (i + 1) % n } else { i };
+        best = Some((x, at));
+    }
+    let (x, mut at) = best?;
+    let hit = Point::new(x, m.y);
+    // a reflex point inside the triangle between `m`, the hit and the chosen point would
+    // block the view. the one nearest the ray in angle is visible instead.
+    let p = points[ring[at]];
+    if p != hit {
+        let mut best_angle = f64::INFINITY;
+        let mut best_dist = f64::INFINITY;
+        for i in 0..n {
+            let q = points[ring[i]];
+            let (prev, next) = (points[ring[(i + n - 1) % n]], points[ring[(i + 1) % n]]);
+            let reflex = (q - prev).cross(&(next - q)) <= 0.0;
+            if q == p || !reflex || !inside_triangle(m, &hit, &p, &q) {
+                continue;
+            }
+            let angle = ((q.y - m.y) / (q.x - m.x)).atan().abs();
+            let dist = q.dist(m);
+            if angle < best_angle || (angle == best_angle && dist < best_dist) {
+                best_angle = angle;
+                best_dist = dist;
+                at = i;
+            }
+        }
+    }
+    Some(at)
+}
+
+// clips ears from a clockwise ring of indices until only a triangle remains.
+fn clip_ears(points: &[Point], mut ring: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::new();
+    let mut i = 0;
+    let mut stalled = 0;
+    while ring.len() > 3 {
+        let n = ring.len();
+        i %= n;
+        let (a, b, c) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let turn = (points[b] - points[a]).cross(&(points[c] - points[b]));
+        if turn == 0.0 && (points[b] - points[a]).dot(&(points[c] - points[b])) <= 0.0 {
+            // a spike that doubles straight back, or a repeated point, encloses nothing.
+            ring.remove(i);
+            stalled = 0;
+            continue;
+        }
+        // a point along a straight edge can't be the tip of an ear, but stays in the ring so
+        // the ears either side take it as a corner. with no proper ear left, from a slightly
+        // broken outline, take any convex corner.
+        let forced = stalled > n;
+        if turn > 0.0 && (forced || is_ear(points, &ring, a, b, c)) {
+            triangles.push([a, b, c]);
+            ring.remove(i);
+            stalled = 0;
+            i = (i + n - 2) % (n - 1);
+        }
+        else if stalled > 2 * n {
+            break;
+        }
+        else {
+            i = (i + 1) % n;
+            stalled += 1;
+        }
+    }
+    if ring.len() == 3 && (points[ring[1]] - points[ring[0]]).cross(&(points[ring[2]] - points[ring[0]])) > 0.0 {
+        triangles.push([ring[0], ring[1], ring[2]]);
+    }
+    triangles
+}
+
+// true if no other point of the ring is inside or on the triangle a, b, c.
+fn is_ear(points: &[Point], ring: &[usize], a: usize, b: usize, c: usize) -> bool {
+    let (pa, pb, pc) = (points[a], points[b], points[c]);
+    ring.iter()
+        .map(|&k| points[k])
+        .filter(|q| *q != pa && *q != pb && *q != pc)
+        .all(|q| !inside_triangle(&pa, &pb, &pc, &q))
+}
+
+// true if `p` is inside or on the edges of triangle a, b, c, either way round.
+fn inside_triangle(a: &Point, b: &Point, c: &Point, p: &Point) -> bool {
+    let d0 = (*b - *a).cross(&(*p - *a));
+    let d1 = (*c - *b).cross(&(*p - *b));
+    let d2 = (*a - *c).cross(&(*p - *c));
+    (d0 >= 0.0 && d1 >= 0.0 && d2 >= 0.0) || (d0 <= 0.0 && d1 <= 0.0 && d2 <= 0.0)
+}
+
+// Lawson flips: swaps the shared edge of any two triangles where one has the far point of the
+// other inside its circumcircle, until none do. `fixed` edges are never flipped.
+// edges are checked in sorted order, then the four around each flip are queued for another look,
+// so the result doesn't depend on hashing.
+fn flip_edges(points: &[Point], triangles: &mut [[usize; 3]], fixed: &HashSet<(usize, usize)>) {
+    let key = |a: usize, b: usize| (a.min(b), a.max(b));
+    let mut edges: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+    for (i, t) in triangles.iter().enumerate() {
+        for j in 0..3 {
+            edges.entry(key(t[j], t[(j + 1) % 3])).or_default().push(i);
+        }
+    }
+    let mut queue: VecDeque<(usize, usize)> = edges.keys().cloned().collect();
+    let mut flips = 0;
+    let max_flips = triangles.len() * triangles.len() + 1;
+    while let Some(edge) = queue.pop_front() {
+        if flips >= max_flips {
+            break;
+        }
+        let (i0, i1) = match edges.get(&edge) {
+            Some(tris) if tris.len() == 2 && !fixed.contains(&edge) => (tris[0], tris[1]),
+            _ => continue,
+        };
+        let (t0, t1) = (triangles[i0], triangles[i1]);
+        // rotate t0 to (a, b, c) with the shared edge a, b; t1 is then (b, a, d).
+        let j = (0..3).find(|&j| !(t0[j] == edge.0 || t0[j] == edge.1)).unwrap();
+        let (a, b, c) = (t0[(j + 1) % 3], t0[(j + 2) % 3], t0[j]);
+        let d = *t1.iter().find(|&&v| v != a && v != b).unwrap();
+        if points[d] == points[c] || !in_circumcircle(&points[a], &points[b], &points[c], &points[d]) {
+            continue;
+        }
+        // the new triangles must still run clockwise, or the quad wasn't convex.
+        let new0 = [a, d, c];
+        let new1 = [d, b, c];
+        let clockwise = |t: &[usize; 3]| (points[t[1]] - points[t[0]]).cross(&(points[t[2]] - points[t[0]])) > 0.0;
+        if !clockwise(&new0) || !clockwise(&new1) {
+            continue;
+        }
+        triangles[i0] = new0;
+        triangles[i1] = new1;
+        flips += 1;
+
+        // a-b becomes c-d, and the edges a-d and b-c swap sides.
+        edges.remove(&edge);
+        edges.insert(key(c, d), vec![i0, i1]);
+        if let Some(tris) = edges.get_mut(&key(a, d)) {
+            for t in tris.iter_mut().filter(|t| **t == i1) {
+                *t = i0;
+            }
+        }
+        if let Some(tris) = edges.get_mut(&key(b, c)) {
+            for t in tris.iter_mut().filter(|t| **t == i0) {
+                *t = i1;
+            }
+        }
+        queue.extend(vec![key(a, d), key(d, b), key(b, c), key(c, a)]);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use geom::triangle::Triangle;
+
+    fn total_area(points: &[Point], triangles: &[[usize; 3]]) -> f64 {
+        triangles.iter().fold(0.0, |sum, t| {
+            let triangle = Triangle::new(points[t[0]], points[t[1]], points[t[2]]);
+            assert!(triangle.signed_area() > 0.0);
+            sum + triangle.area()
+        })
+    }
+
+    fn comb() -> Polygon {
+        // three teeth hanging down, anticlockwise on screen.
+        Polygon::new(vec![Point::new(0.0, 0.0), Point::new(0.0, 50.0), Point::new(10.0, 50.0), Point::new(10.0, 10.0),
+                          Point::new(20.0, 10.0), Point::new(20.0, 50.0), Point::new(30.0, 50.0), Point::new(30.0, 10.0),
+                          Point::new(40.0, 10.0), Point::new(40.0, 50.0), Point::new(50.0, 50.0), Point::new(50.0, 0.0)])
+    }
+
+    #[test]
+    fn test_triangulate() {
+        let square = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)]);
+        assert_eq!(triangulate(&square).len(), 2);
+
+        let comb = comb();
+        let triangles = triangulate(&comb);
+        assert_eq!(triangles.len(), comb.len() - 2);
+        assert!(approx_eq(total_area(&comb.points, &triangles), comb.area()));
+        // no triangle reaches into the gaps between the teeth.
+        for t in triangles.iter() {
+            let centroid = Triangle::new(comb.points[t[0]], comb.points[t[1]], comb.points[t[2]]).centroid();
+            assert!(comb.contains_point(&centroid));
+        }
+    }
+
+    #[test]
+    fn test_collinear() {
+        // a square with a point halfway along each side, starting from one of them.
+        let square = Polygon::new(vec![Point::new(5.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 5.0), Point::new(10.0, 10.0),
+                                       Point::new(5.0, 10.0), Point::new(0.0, 10.0), Point::new(0.0, 5.0), Point::new(0.0, 0.0)]);
+        let triangles = triangulate(&square);
+        assert_eq!(triangles.len(), square.len() - 2);
+        assert!(approx_eq(total_area(&square.points, &triangles), 100.0));
+        for i in 0..square.len() {
+            assert!(triangles.iter().any(|t| t.contains(&i)));
+        }
+        let constrained = triangulate_constrained(&square, &[]);
+        let n = square.len();
+        for i in 0..n {
+            let (a, b) = (i, (i + 1) % n);
+            assert!(constrained.iter().any(|t| t.contains(&a) && t.contains(&b)));
+        }
+    }
+
+    #[test]
+    fn test_holes() {
+        let outline = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)]);
+        let holes = vec![Polygon::regular(30.0, 50.0, 10.0, 6, 0.0),
+                         Polygon::new(vec![Point::new(60.0, 20.0), Point::new(80.0, 20.0), Point::new(80.0, 80.0), Point::new(60.0, 80.0)])];
+        let points = flatten_holes(&outline, &holes);
+        assert_eq!(points.len(), 14);
+        let triangles = triangulate_with_holes(&outline, &holes);
+        // n points and h holes make n + 2h - 2 triangles.
+        assert_eq!(triangles.len(), 14 + 4 - 2);
+        let area = outline.area() - holes[0].area() - holes[1].area();
+        assert!(approx_eq(total_area(&points, &triangles), area));
+        for t in triangles.iter() {
+            let centroid = Triangle::new(points[t[0]], points[t[1]], points[t[2]]).centroid();
+            assert!(!holes[0].contains_point(&centroid) && !holes[1].contains_point(&centroid));
+        }
+    }
+
+    #[test]
+    fn test_constrained() {
+        // a long thin strip of points along both edges: plain ear clipping fans out slivers.
+        let mut outline: Vec<Point> = (0..=10).map(|i| Point::new(i as f64 * 10.0, 0.0)).collect();
+        outline.extend((0..=10).rev().map(|i| Point::new(i as f64 * 10.0 + 5.0, 8.0)));
+        let outline = Polygon::new(outline);
+        let plain = triangulate(&outline);
+        let constrained = triangulate_constrained(&outline, &[]);
+        assert_eq!(plain.len(), constrained.len());
+        assert!(approx_eq(total_area(&outline.points, &constrained), outline.area()));
+        assert_eq!(constrained, triangulate_constrained(&outline, &[]));
+        let smallest_angle = |triangles: &[[usize; 3]]| {
+            triangles.iter().fold(f64::INFINITY, |min, t| {
+                (0..3).fold(min, |min, j| {
+                    let (a, b, c) = (outline.points[t[j]], outline.points[t[(j + 1) % 3]], outline.points[t[(j + 2) % 3]]);
+                    min.min((b - a).normalize().dot(&(c - a).normalize()).acos())
+                })
+            })
+        };
+        assert!(smallest_angle(&constrained) > smallest_angle(&plain));
+
+        // every outline edge is still there.
+        let n = outline.len();
+        for i in 0..n {
+            let (a, b) = (i, (i + 1) % n);
+            assert!(constrained.iter().any(|t| t.contains(&a) && t.contains(&b)));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use super::point::Point;
+use super::rect::Rect;
+use super::polygon::Polygon;
+use super::delaunay::Delaunay;
+
+// cells are clockwise on screen and share their edges exactly.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Voronoi {
+    pub sites: Vec<Point>,
+    pub bounds: Rect,
+    pub cells: Vec<Polygon>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Voronoi {
+    // sites outside bounds still shape the cells inside it.
+    pub fn new(sites: &[Point], bounds: &Rect) -> Voronoi {
+        let delaunay = Delaunay::new(sites);
+        let mut cells = Vec::with_capacity(sites.len());
+        let mut neighbors = Vec::with_capacity(sites.len());
+        for (i, site) in sites.iter().enumerate() {
+            // the Delaunay neighbors are enough to carve out a cell. Without any, as for
+            // repeated sites or sites all on a line, every other site has to be tried.
+            let mut candidates = delaunay.neighbors(i);
+            if candidates.is_empty() {
+                candidates = (0..sites.len()).filter(|&j| sites[j] != *site).collect();
+            }
+            let mut cell = Polygon::from_rect(bounds).points;
+            for &j in candidates.iter() {
+                cell = clip_to_nearer(&cell, site, &sites[j]);
+            }
+            // neighbors are those whose bisector still forms part of the outline.
+            let near: Vec<usize> = candidates.into_iter()
+                .filter(|&j| on_bisector(&cell, site, &sites[j]))
+                .collect();
+            cells.push(Polygon::new(cell));
+            neighbors.push(near);
+        }
+        Voronoi {
+            sites: sites.to_vec(),
+            bounds: *bounds,
+            cells: cells,
+            neighbors: neighbors,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.neighbors[i]
+    }
+
+    // None if there are no sites or p isn't finite.
+    pub fn find(&self, p: &Point) -> Option<usize> {
+        let mut best = None;
+        let mut best_dist = f64::INFINITY;
+        for (i, site) in self.sites.iter().enumerate() {
+            let d = p.dist(site);
+            if d < best_dist {
+                best = Some(i);
+                best_dist = d;
+            }
+        }
+        best
+    }
+
+    // one step of lloyd relaxation.
+    pub fn relax(&self) -> Voronoi {
+        let sites: Vec<Point> = self.sites.iter().zip(self.cells.iter())
+            .map(|(site, cell)| if cell.len() < 3 { *site } else { cell.centroid() })
+            .collect();
+        Voronoi::new(&sites, &self.bounds)
+    }
+}
+
+pub fn lloyd(points: &[Point], bounds: &Rect, iterations: i32) -> Vec<Point> {
+    let mut voronoi = Voronoi::new(points, bounds);
+    for _ in 0..iterations {
+        voronoi = voronoi.relax();
+    }
+    voronoi.sites
+}
+
+// the part of `cell` at least as near `site` as `other`.
+fn clip_to_nearer(cell: &[Point], site: &Point, other: &Point) -> Vec<Point> {
+    let mid = site.lerp(other, 0.5);
+    let normal = *other - *site;
+    let side = |p: &Point| (*p - mid).dot(&normal);
+    let mut output = Vec::new();
+    for i in 0..cell.len() {
+        let current = cell[i];
+        let prev = cell[(i + cell.len() - 1) % cell.len()];
+        let (s0, s1) = (side(&prev), side(&current));
+        if (s0 < 0.0) != (s1 < 0.0) {
+            output.push(prev.lerp(&current, s0 / (s0 - s1)));
+        }
+        if s1 < 0.0 {
+            output.push(current);
+        }
+    }
+    output
+}
+
+// true if an edge of `cell` lies along the bisector of `site` and `other`.
+fn on_bisector(cell: &[Point], site: &Point, other: &Point) -> bool {
+    let mid = site.lerp(other, 0.5);
+    let normal = *other - *site;
+    let tolerance = 1e-9 * normal.dot(&normal).max(1.0);
+    let n = cell.len();
+    (0..n).any(|i| {
+        let (a, b) = (cell[i], cell[(i + 1) % n]);
+        a.dist(&b) > 1e-9 && (a - mid).dot(&normal).abs() <= tolerance && (b - mid).dot(&normal).abs() <= tolerance
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use random::Random;
+
+    fn bounds() -> Rect {
+        Rect::new(0.0, 0.0, 100.0, 100.0)
+    }
+
+    fn random_sites(seed: usize, count: usize) -> Vec<Point> {
+        let mut rand = Random::from_seed(seed);
+        (0..count).map(|_| bounds().random_point(&mut rand)).collect()
+    }
+
+    #[test]
+    fn test_grid_cells() {
+        let sites = vec![Point::new(25.0, 25.0), Point::new(75.0, 25.0), Point::new(25.0, 75.0), Point::new(75.0, 75.0)];
+        let v = Voronoi::new(&sites, &bounds());
+        for cell in v.cells.iter() {
+            assert!(approx_eq(cell.area(), 2500.0));
+            assert!(cell.is_clockwise());
+        }
+        assert_eq!(v.neighbors(0), &[1, 2]);
+        assert_eq!(v.neighbors(3), &[1, 2]);
+        assert_eq!(v.find(&Point::new(90.0, 10.0)), Some(1));
+        assert_eq!(v.find(&Point::new(f64::NAN, 10.0)), None);
+    }
+
+    #[test]
+    fn test_cells_tile_bounds() {
+        let sites = random_sites(3, 80);
+        let v = Voronoi::new(&sites, &bounds());
+        let area = v.cells.iter().fold(0.0, |sum, c| sum + c.area());
+        assert!(approx_eq(area, bounds().area()));
+        for (i, cell) in v.cells.iter().enumerate() {
+            assert!(cell.contains_point(&sites[i]));
+            // every corner is as near this site as any other.
+            for p in cell.points.iter() {
+                let d = p.dist(&sites[i]);
+                assert!(sites.iter().all(|s| s.dist(p) >= d - 1e-6));
+            }
+            for &j in v.neighbors(i) {
+                assert!(v.neighbors(j).contains(&i));
+            }
+        }
+    }
+
+    #[test]
+    fn test_degenerate_sites() {
+        // sites on a line give bands.
+        let sites = vec![Point::new(10.0, 50.0), Point::new(50.0, 50.0), Point::new(90.0, 50.0)];
+        let v = Voronoi::new(&sites, &bounds());
+        assert!(approx_eq(v.cells[1].area(), 4000.0));
+        assert_eq!(v.neighbors(1), &[0, 2]);
+        let v = Voronoi::new(&[Point::new(50.0, 50.0)], &bounds());
+        assert!(approx_eq(v.cells[0].area(), 10000.0));
+    }
+
+    #[test]
+    fn test_lloyd() {
+        let sites = random_sites(4, 40);
+        // relaxation evens out the cell sizes.
+        let spread = |v: &Voronoi| {
+            let areas: Vec<f64> = v.cells.iter().map(|c| c.area()).collect();
+            areas.iter().cloned().fold(0.0, f64::max) - areas.iter().cloned().fold(f64::INFINITY, f64::min)
+        };
+        let before = Voronoi::new(&sites, &bounds());
+        let relaxed = lloyd(&sites, &bounds(), 10);
+        assert_eq!(relaxed.len(), sites.len());
+        assert!(relaxed.iter().all(|p| bounds().contains_point(p)));
+        assert!(spread(&Voronoi::new(&relaxed, &bounds())) < spread(&before) / 2.0);
+    }
+}
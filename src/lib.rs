@@ -4,5 +4,7 @@ pub mod geom;
 pub mod gif;
 pub mod file;
 pub mod math;
+pub mod plot;
 pub mod random;
+pub mod sketch;
 pub mod util;
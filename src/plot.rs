@@ -0,0 +1,399 @@
+extern crate cairo;
+
+use self::cairo::Context;
+use canvas::BitContext;
+use color::Color;
+use geom::point::Point;
+use math::map;
+
+enum SeriesData {
+    Function(Box<dyn Fn(f64) -> f64>),
+    Line(Vec<Point>),
+    Scatter(Vec<Point>),
+}
+
+struct Series {
+    data: SeriesData,
+    color: Color,
+    label: String,
+}
+
+// ranges and tick spacing are worked out from the data unless set explicitly.
+pub struct Plot {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+    pub x_range: Option<(f64, f64)>,
+    pub y_range: Option<(f64, f64)>,
+    pub x_tick: Option<f64>,
+    pub y_tick: Option<f64>,
+    pub tick_size: f64,
+    pub font_size: f64,
+    pub resolution: i32,
+    pub show_grid: bool,
+    pub show_labels: bool,
+    pub show_legend: bool,
+    pub axis_color: Color,
+    pub grid_color: Color,
+    pub label_color: Color,
+    series: Vec<Series>,
+}
+
+impl Plot {
+    pub fn new(x: f64, y: f64, w: f64, h: f64) -> Plot {
+        Plot {
+            x: x,
+            y: y,
+            w: w,
+            h: h,
+            x_range: None,
+            y_range: None,
+            x_tick: None,
+            y_tick: None,
+            tick_size: 5.0,
+            font_size: 10.0,
+            resolution: w as i32,
+            show_grid: true,
+            show_labels: true,
+            show_legend: true,
+            axis_color: Color::black(),
+            grid_color: Color::grey(0.85),
+            label_color: Color::black(),
+            series: Vec::new(),
+        }
+    }
+
+    pub fn set_x_range(&mut self, min: f64, max: f64) {
+        self.x_range = Some((min, max));
+    }
+
+    pub fn set_y_range(&mut self, min: f64, max: f64) {
+        self.y_range = Some((min, max));
+    }
+
+    pub fn set_ticks(&mut self, x_tick: f64, y_tick: f64) {
+        self.x_tick = Some(x_tick);
+        self.y_tick = Some(y_tick);
+    }
+
+    // sampled resolution times across the x range when drawn.
+    pub fn add_function<F: Fn(f64) -> f64 + 'static>(&mut self, f: F, color: Color, label: &str) {
+        self.add_series(SeriesData::Function(Box::new(f)), color, label);
+    }
+
+    pub fn add_line(&mut self, points: Vec<Point>, color: Color, label: &str) {
+        self.add_series(SeriesData::Line(points), color, label);
+    }
+
+    pub fn add_scatter(&mut self, points: Vec<Point>, color: Color, label: &str) {
+        self.add_series(SeriesData::Scatter(points), color, label);
+    }
+
+    fn add_series(&mut self, data: SeriesData, color: Color, label: &str) {
+        self.series.push(Series {
+            data: data,
+            color: color,
+            label: label.to_string(),
+        });
+    }
+
+    // falls back to 0..1 when only functions have been added.
+    pub fn get_x_range(&self) -> (f64, f64) {
+        if let Some(range) = self.x_range {
+            return range;
+        }
+        let mut xs = Vec::new();
+        for series in self.series.iter() {
+            match series.data {
+                SeriesData::Line(ref points) | SeriesData::Scatter(ref points) => {
+                    xs.extend(points.iter().map(|p| p.x));
+                }
+                SeriesData::Function(_) => {}
+            }
+        }
+        pad_range(extent(&xs).unwrap_or((0.0, 1.0)))
+    }
+
+    pub fn get_y_range(&self) -> (f64, f64) {
+        if let Some(range) = self.y_range {
+            return range;
+        }
+        let x_range = self.get_x_range();
+        let mut ys = Vec::new();
+        for series in self.series.iter() {
+            match series.data {
+                SeriesData::Line(ref points) | SeriesData::Scatter(ref points) => {
+                    ys.extend(points.iter().map(|p| p.y));
+                }
+                SeriesData::Function(ref f) => {
+                    ys.extend(self.sample(f, x_range).iter().map(|p| p.y));
+                }
+            }
+        }
+        pad_range(extent(&ys).unwrap_or((0.0, 1.0)))
+    }
+
+    pub fn to_canvas(&self, p: &Point) -> Point {
+        self.map_point(p, self.get_x_range(), self.get_y_range())
+    }
+
+    // working out auto ranges means scanning every series, so draw does it once up front.
+    fn map_point(&self, p: &Point, x_range: (f64, f64), y_range: (f64, f64)) -> Point {
+        Point::new(map(p.x, x_range.0, x_range.1, self.x, self.x + self.w),
+                   map(p.y, y_range.0, y_range.1, self.y + self.h, self.y))
+    }
+
+    pub fn draw(&self, context: &Context) {
+        let x_range = self.get_x_range();
+        let y_range = self.get_y_range();
+        let (x_min, x_max) = x_range;
+        let (y_min, y_max) = y_range;
+        let x_tick = self.x_tick.unwrap_or_else(|| nice_step(x_min, x_max, 10));
+        let y_tick = self.y_tick.unwrap_or_else(|| nice_step(y_min, y_max, 10));
+        let x_scale = self.w / (x_max - x_min);
+        let y_scale = self.h / (y_max - y_min);
+
+        context.save();
+        context.set_line_width(1.0);
+
+        if self.show_grid && ticks_fit(x_min, x_max, x_tick) && ticks_fit(y_min, y_max, y_tick) {
+            context.set_source_color(&self.grid_color);
+            context.grid_offset(self.x, self.y, self.w, self.h,
+                                x_tick * x_scale, y_tick * y_scale,
+                                -x_min * x_scale, self.h + y_min * y_scale);
+        }
+
+        // axes sit at zero when it is in range, otherwise along the low edge.
+        let axis = self.map_point(&Point::new(
+            if x_min <= 0.0 && x_max >= 0.0 { 0.0 } else { x_min },
+            if y_min <= 0.0 && y_max >= 0.0 { 0.0 } else { y_min }), x_range, y_range);
+        context.set_source_color(&self.axis_color);
+        context.move_to(self.x, axis.y);
+        context.line_to(self.x + self.w, axis.y);
+        context.move_to(axis.x, self.y);
+        context.line_to(axis.x, self.y + self.h);
+        for value in ticks(x_min, x_max, x_tick) {
+            let p = self.map_point(&Point::new(value, 0.0), x_range, y_range);
+            context.move_to(p.x, axis.y - self.tick_size);
+            context.line_to(p.x, axis.y + self.tick_size);
+        }
+        for value in ticks(y_min, y_max, y_tick) {
+            let p = self.map_point(&Point::new(0.0, value), x_range, y_range);
+            context.move_to(axis.x - self.tick_size, p.y);
+            context.line_to(axis.x + self.tick_size, p.y);
+        }
+        context.stroke();
+
+        if self.show_labels {
+            context.set_source_color(&self.label_color);
+            context.set_font_size(self.font_size);
+            for value in ticks(x_min, x_max, x_tick) {
+                let label = format_tick(value, x_tick);
+                let extents = context.text_extents(&label);
+                let p = self.map_point(&Point::new(value, 0.0), x_range, y_range);
+                context.move_to(p.x - extents.width / 2.0, axis.y + self.tick_size + self.font_size + 2.0);
+                context.show_text(&label);
+            }
+            for value in ticks(y_min, y_max, y_tick) {
+                let label = format_tick(value, y_tick);
+                let extents = context.text_extents(&label);
+                let p = self.map_point(&Point::new(0.0, value), x_range, y_range);
+                context.move_to(axis.x - self.tick_size - extents.width - 4.0, p.y + extents.height / 2.0);
+                context.show_text(&label);
+            }
+        }
+
+        context.rectangle(self.x, self.y, self.w, self.h);
+        context.clip();
+        for series in self.series.iter() {
+            context.set_source_color(&series.color);
+            match series.data {
+                SeriesData::Function(ref f) => {
+                    let points: Vec<Point> = self.sample(f, x_range).iter().map(|p| self.map_point(p, x_range, y_range)).collect();
+                    context.stroke_path(&points, false);
+                }
+                SeriesData::Line(ref points) => {
+                    let points: Vec<Point> = points.iter().map(|p| self.map_point(p, x_range, y_range)).collect();
+                    context.stroke_path(&points, false);
+                }
+                SeriesData::Scatter(ref points) => {
+                    let points: Vec<Point> = points.iter().map(|p| self.map_point(p, x_range, y_range)).collect();
+                    context.points(&points, 2.5);
+                }
+            }
+        }
+        context.restore();
+
+        if self.show_legend {
+            self.draw_legend(context);
+        }
+    }
+
+    fn draw_legend(&self, context: &Context) {
+        let labelled: Vec<&Series> = self.series.iter().filter(|s| !s.label.is_empty()).collect();
+        if labelled.is_empty() {
+            return;
+        }
+        context.save();
+        context.set_font_size(self.font_size);
+        let line_height = self.font_size * 1.5;
+        let swatch = self.font_size * 2.0;
+        let mut width: f64 = 0.0;
+        for series in labelled.iter() {
+            width = width.max(context.text_extents(&series.label).width);
+        }
+        let w = swatch + width + self.font_size * 1.5;
+        let h = line_height * labelled.len() as f64 + self.font_size * 0.5;
+        let x = self.x + self.w - w - 10.0;
+        let y = self.y + 10.0;
+
+        context.set_source_color(&Color::rgba(1.0, 1.0, 1.0, 0.8));
+        context.fill_rectangle(x, y, w, h);
+        context.set_source_color(&self.axis_color);
+        context.stroke_rectangle(x, y, w, h);
+        for (i, series) in labelled.iter().enumerate() {
+            let yy = y + line_height * (i as f64 + 1.0) - line_height * 0.25;
+            context.set_source_color(&series.color);
+            context.line(x + self.font_size * 0.5, yy - self.font_size * 0.3,
+                         x + self.font_size * 0.5 + swatch, yy - self.font_size * 0.3);
+            context.set_source_color(&self.label_color);
+            context.move_to(x + swatch + self.font_size, yy);
+            context.show_text(&series.label);
+        }
+        context.restore();
+    }
+
+    fn sample(&self, f: &dyn Fn(f64) -> f64, x_range: (f64, f64)) -> Vec<Point> {
+        let (x_min, x_max) = x_range;
+        let res = self.resolution.max(1);
+        (0..res + 1)
+            .map(|i| {
+                let x = map(i as f64, 0.0, res as f64, x_min, x_max);
+                Point::new(x, f(x))
+            })
+            .filter(|p| p.y.is_finite())
+            .collect()
+    }
+}
+
+// 1, 2 or 5 times a power of ten, giving roughly count ticks.
+pub fn nice_step(min: f64, max: f64, count: i32) -> f64 {
+    let raw = (max - min).abs() / count.max(1) as f64;
+    if raw == 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10.0_f64.powf(raw.log10().floor());
+    let residual = raw / magnitude;
+    let nice = if residual < 1.5 {
+        1.0
+    }
+    else if residual < 3.0 {
+        2.0
+    }
+    else if residual < 7.0 {
+        5.0
+    }
+    else {
+        10.0
+    };
+    nice * magnitude
+}
+
+pub const MAX_TICKS: usize = 1000;
+
+// a step that isn't positive and finite gives no ticks.
+pub fn ticks(min: f64, max: f64, step: f64) -> Vec<f64> {
+    let mut result = Vec::new();
+    if step <= 0.0 || !step.is_finite() {
+        return result;
+    }
+    let first = (min / step).ceil();
+    let count = (max / step + 1e-9).floor() - first + 1.0;
+    if count.is_nan() || count < 1.0 {
+        return result;
+    }
+    for i in 0..count.min(MAX_TICKS as f64) as usize {
+        result.push((first + i as f64) * step);
+    }
+    result
+}
+
+// true if a grid at `step` has no more than MAX_TICKS lines across the range.
+fn ticks_fit(min: f64, max: f64, step: f64) -> bool {
+    step > 0.0 && step.is_finite() && (max - min) / step <= MAX_TICKS as f64
+}
+
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = if step >= 1.0 { 0 } else { (-step.log10()).ceil() as usize };
+    let value = if value.abs() < step * 1e-9 { 0.0 } else { value };
+    format!("{:.*}", decimals, value)
+}
+
+fn extent(values: &[f64]) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().cloned().fold(values[0], f64::min);
+    let max = values.iter().cloned().fold(values[0], f64::max);
+    Some((min, max))
+}
+
+fn pad_range(range: (f64, f64)) -> (f64, f64) {
+    if range.0 == range.1 {
+        (range.0 - 1.0, range.1 + 1.0)
+    }
+    else {
+        range
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(0.0, 10.0, 10), 1.0);
+        assert_eq!(nice_step(0.0, 100.0, 10), 10.0);
+        assert_eq!(nice_step(0.0, 25.0, 10), 2.0);
+        assert_eq!(nice_step(-1.0, 1.0, 10), 0.2);
+        assert!(approx_eq(nice_step(0.0, 0.04, 10), 0.005));
+        assert_eq!(nice_step(5.0, 5.0, 10), 1.0);
+    }
+
+    #[test]
+    fn test_ticks() {
+        assert_eq!(ticks(0.0, 5.0, 1.0), vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(ticks(-2.5, 2.5, 2.0), vec![-2.0, 0.0, 2.0]);
+        assert_eq!(ticks(0.1, 0.9, 1.0), Vec::<f64>::new());
+        assert!(ticks(0.0, 1.0, 0.0).is_empty());
+        assert!(ticks(0.0, 1.0, -0.5).is_empty());
+        assert!(ticks(f64::NAN, 1.0, 0.5).is_empty());
+        assert_eq!(ticks(0.0, 1.0, 1e-12).len(), MAX_TICKS);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut plot = Plot::new(0.0, 0.0, 200.0, 100.0);
+        plot.add_line(vec![Point::new(-5.0, 2.0), Point::new(5.0, 8.0)], Color::red(), "data");
+        assert_eq!(plot.get_x_range(), (-5.0, 5.0));
+        assert_eq!(plot.get_y_range(), (2.0, 8.0));
+        plot.add_function(|x| x * x, Color::blue(), "square");
+        assert_eq!(plot.get_y_range(), (0.0, 25.0));
+        plot.set_y_range(-1.0, 1.0);
+        assert_eq!(plot.get_y_range(), (-1.0, 1.0));
+    }
+
+    #[test]
+    fn test_to_canvas() {
+        let mut plot = Plot::new(10.0, 20.0, 200.0, 100.0);
+        plot.set_x_range(0.0, 10.0);
+        plot.set_y_range(0.0, 5.0);
+        assert_eq!(plot.to_canvas(&Point::new(0.0, 0.0)), Point::new(10.0, 120.0));
+        assert_eq!(plot.to_canvas(&Point::new(10.0, 5.0)), Point::new(210.0, 20.0));
+        assert_eq!(plot.to_canvas(&Point::new(5.0, 2.5)), Point::new(110.0, 70.0));
+    }
+}
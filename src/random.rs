@@ -40,4 +40,8 @@ impl Random {
         self.float(0.0, 1.0) < weight
     }
 
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        self.rng.shuffle(values);
+    }
+
 }
@@ -0,0 +1,344 @@
+extern crate cairo;
+
+use self::cairo::Context;
+use canvas::BitContext;
+use geom::{ dist, polygon_points };
+use geom::point::Point;
+use geom::bezier::CubicBezier;
+use geom::spline::CatmullRom;
+use math::TWO_PI;
+use random::Random;
+
+// all randomness comes from rand, so a seeded sketch draws the same way every time.
+pub struct Sketch {
+    pub roughness: f64,
+    pub bowing: f64,
+    pub max_offset: f64,
+    pub overshoot: f64,
+    pub double_stroke: bool,
+    pub hachure_gap: f64,
+    pub hachure_angle: f64,
+    pub rand: Random,
+}
+
+impl Sketch {
+    pub fn new(roughness: f64, rand: Random) -> Sketch {
+        Sketch {
+            roughness: roughness,
+            bowing: 1.0,
+            max_offset: 2.0,
+            overshoot: 2.0,
+            double_stroke: true,
+            hachure_gap: 4.0,
+            hachure_angle: -0.7,
+            rand: rand,
+        }
+    }
+
+    pub fn line(&mut self, context: &Context, x0: f64, y0: f64, x1: f64, y1: f64) {
+        let points = vec![Point::new(x0, y0), Point::new(x1, y1)];
+        self.stroke_path(context, &points, false);
+    }
+
+    pub fn stroke_rectangle(&mut self, context: &Context, x: f64, y: f64, w: f64, h: f64) {
+        let points = vec![Point::new(x, y), Point::new(x + w, y), Point::new(x + w, y + h), Point::new(x, y + h)];
+        self.stroke_path(context, &points, true);
+    }
+
+    pub fn fill_rectangle(&mut self, context: &Context, x: f64, y: f64, w: f64, h: f64) {
+        let points = vec![Point::new(x, y), Point::new(x + w, y), Point::new(x + w, y + h), Point::new(x, y + h)];
+        self.fill_path(context, &points);
+    }
+
+    pub fn stroke_circle(&mut self, context: &Context, x: f64, y: f64, r: f64) {
+        self.stroke_ellipse(context, x, y, r, r);
+    }
+
+    pub fn fill_circle(&mut self, context: &Context, x: f64, y: f64, r: f64) {
+        self.fill_ellipse(context, x, y, r, r);
+    }
+
+    pub fn stroke_ellipse(&mut self, context: &Context, x: f64, y: f64, xr: f64, yr: f64) {
+        let points = self.ellipse_points(x, y, xr, yr, 1.0);
+        context.stroke_catmull_rom(&points, CatmullRom::Uniform, false);
+        if self.double_stroke {
+            let points = self.ellipse_points(x, y, xr, yr, 1.5);
+            context.stroke_catmull_rom(&points, CatmullRom::Uniform, false);
+        }
+    }
+
+    pub fn fill_ellipse(&mut self, context: &Context, x: f64, y: f64, xr: f64, yr: f64) {
+        let steps = ellipse_steps(xr, yr);
+        let points: Vec<Point> = (0..steps)
+            .map(|i| {
+                let angle = TWO_PI * i as f64 / steps as f64;
+                Point::new(x + angle.cos() * xr, y + angle.sin() * yr)
+            })
+            .collect();
+        self.hachure(context, &points);
+    }
+
+    pub fn stroke_polygon(&mut self, context: &Context, x: f64, y: f64, r: f64, sides: i32, rotation: f64) {
+        let points = polygon_points(x, y, r, sides, rotation);
+        self.stroke_path(context, &points, true);
+    }
+
+    pub fn fill_polygon(&mut self, context: &Context, x: f64, y: f64, r: f64, sides: i32, rotation: f64) {
+        let points = polygon_points(x, y, r, sides, rotation);
+        self.fill_path(context, &points);
+    }
+
+    pub fn stroke_path(&mut self, context: &Context, points: &[Point], close: bool) {
+        if points.len() < 2 {
+            return;
+        }
+        for curve in self.path_curves(points, close).iter() {
+            context.cubic_bezier(curve);
+        }
+        context.stroke();
+    }
+
+    // leaves the outline undrawn.
+    pub fn fill_path(&mut self, context: &Context, points: &[Point]) {
+        self.hachure(context, points);
+    }
+
+    fn hachure(&mut self, context: &Context, points: &[Point]) {
+        let lines = hachure_lines(points, self.hachure_gap, self.hachure_angle);
+        for &(p0, p1) in lines.iter() {
+            let curve = self.rough_line(p0.x, p0.y, p1.x, p1.y, false);
+            context.cubic_bezier(&curve);
+        }
+        context.stroke();
+    }
+
+    // the rough strokes along each edge of a path, with the overlay straight after each one.
+    fn path_curves(&mut self, points: &[Point], close: bool) -> Vec<CubicBezier> {
+        let mut curves = Vec::new();
+        let count = if close { points.len() } else { points.len().saturating_sub(1) };
+        for i in 0..count {
+            let p0 = &points[i];
+            let p1 = &points[(i + 1) % points.len()];
+            curves.push(self.rough_line(p0.x, p0.y, p1.x, p1.y, false));
+            if self.double_stroke {
+                curves.push(self.rough_line(p0.x, p0.y, p1.x, p1.y, true));
+            }
+        }
+        curves
+    }
+
+    // one jittered, bowed bezier from p0 to p1.
+    // the overlay pass uses half the offset so the two strokes stay close.
+    fn rough_line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, overlay: bool) -> CubicBezier {
+        let length = dist(&Point::new(x0, y0), &Point::new(x1, y1));
+        let mut offset = self.max_offset;
+        if offset * offset * 100.0 > length * length {
+            offset = length / 10.0;
+        }
+        if overlay {
+            offset /= 2.0;
+        }
+
+        // push the ends out along the line by a random amount.
+        let (dx, dy) = if length > 0.0 { ((x1 - x0) / length, (y1 - y0) / length) } else { (0.0, 0.0) };
+        let over0 = self.jitter_range(0.0, self.overshoot);
+        let over1 = self.jitter_range(0.0, self.overshoot);
+        let (x0, y0) = (x0 - dx * over0, y0 - dy * over0);
+        let (x1, y1) = (x1 + dx * over1, y1 + dy * over1);
+
+        let diverge = 0.2 + self.rand.float(0.0, 0.2);
+        let bow_x = self.bowing * self.max_offset * (y1 - y0) / 200.0;
+        let bow_y = self.bowing * self.max_offset * (x0 - x1) / 200.0;
+        let bow_x = bow_x + self.jitter(bow_x.abs());
+        let bow_y = bow_y + self.jitter(bow_y.abs());
+
+        let start = Point::new(x0 + self.jitter(offset), y0 + self.jitter(offset));
+        let control0 = Point::new(bow_x + x0 + (x1 - x0) * diverge + self.jitter(offset),
+                                  bow_y + y0 + (y1 - y0) * diverge + self.jitter(offset));
+        let control1 = Point::new(bow_x + x0 + 2.0 * (x1 - x0) * diverge + self.jitter(offset),
+                                  bow_y + y0 + 2.0 * (y1 - y0) * diverge + self.jitter(offset));
+        let end = Point::new(x1 + self.jitter(offset), y1 + self.jitter(offset));
+        CubicBezier::new(start, control0, control1, end)
+    }
+
+    // points around an ellipse with jittered radii, running a little past
+    // the start so the loop overlaps itself like a quick pen stroke.
+    fn ellipse_points(&mut self, x: f64, y: f64, xr: f64, yr: f64, scale: f64) -> Vec<Point> {
+        let steps = ellipse_steps(xr, yr);
+        let increment = TWO_PI / steps as f64;
+        let xr = xr + self.jitter(xr * 0.05 * scale);
+        let yr = yr + self.jitter(yr * 0.05 * scale);
+        let start = self.rand.float(0.0, TWO_PI);
+        let overlap = increment * self.jitter_range(0.1, 0.5);
+        let mut points = Vec::new();
+        let mut angle = start;
+        while angle < start + TWO_PI + overlap {
+            points.push(Point::new(x + angle.cos() * xr + self.jitter(xr * 0.02 * scale),
+                                   y + angle.sin() * yr + self.jitter(yr * 0.02 * scale)));
+            angle += increment;
+        }
+        points
+    }
+
+    fn jitter(&mut self, range: f64) -> f64 {
+        self.jitter_range(-range, range)
+    }
+
+    fn jitter_range(&mut self, min: f64, max: f64) -> f64 {
+        if max <= min {
+            return min * self.roughness;
+        }
+        self.rand.float(min, max) * self.roughness
+    }
+}
+
+// even-odd rule. points that aren't finite are skipped.
+pub fn hachure_lines(points: &[Point], gap: f64, angle: f64) -> Vec<(Point, Point)> {
+    let mut lines = Vec::new();
+    if gap <= 0.0 || !gap.is_finite() {
+        return lines;
+    }
+    // rotate so the hatching is horizontal, scan, then rotate the results back.
+    let rotated: Vec<Point> = points.iter()
+        .map(|p| p.rotate(angle))
+        .filter(|p| p.x.is_finite() && p.y.is_finite())
+        .collect();
+    if rotated.len() < 3 {
+        return lines;
+    }
+    let min_y = rotated.iter().fold(rotated[0].y, |m, p| m.min(p.y));
+    let max_y = rotated.iter().fold(rotated[0].y, |m, p| m.max(p.y));
+    let mut y = min_y + gap / 2.0;
+    while y < max_y {
+        let mut xs = Vec::new();
+        for i in 0..rotated.len() {
+            let p0 = &rotated[i];
+            let p1 = &rotated[(i + 1) % rotated.len()];
+            if (p0.y <= y && p1.y > y) || (p1.y <= y && p0.y > y) {
+                xs.push(p0.x + (y - p0.y) / (p1.y - p0.y) * (p1.x - p0.x));
+            }
+        }
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in xs.chunks(2) {
+            if pair.len() == 2 {
+                lines.push((Point::new(pair[0], y).rotate(-angle), Point::new(pair[1], y).rotate(-angle)));
+            }
+        }
+        y += gap;
+    }
+    lines
+}
+
+fn ellipse_steps(xr: f64, yr: f64) -> i32 {
+    let circumference = TWO_PI * ((xr * xr + yr * yr) / 2.0).sqrt();
+    ((circumference / 20.0) as i32).max(9)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::approx_eq;
+    use math::HALF_PI;
+    use geom::rect::Rect;
+
+    fn sketch(roughness: f64, bowing: f64, seed: usize) -> Sketch {
+        let mut sketch = Sketch::new(roughness, Random::from_seed(seed));
+        sketch.bowing = bowing;
+        sketch
+    }
+
+    // how far `p` is from the line through a and b.
+    fn off_line(a: &Point, b: &Point, p: &Point) -> f64 {
+        ((b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)).abs() / a.dist(b)
+    }
+
+    #[test]
+    fn test_hachure_horizontal() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        let lines = hachure_lines(&square, 10.0, 0.0);
+        assert_eq!(lines.len(), 10);
+        for &(p0, p1) in lines.iter() {
+            assert!(approx_eq(p0.x, 0.0));
+            assert!(approx_eq(p1.x, 100.0));
+            assert!(approx_eq(p0.y, p1.y));
+        }
+    }
+
+    #[test]
+    fn test_hachure_vertical() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        let lines = hachure_lines(&square, 10.0, HALF_PI);
+        assert_eq!(lines.len(), 10);
+        for &(p0, p1) in lines.iter() {
+            assert!(approx_eq(p0.x, p1.x));
+            assert!(approx_eq((p1.y - p0.y).abs(), 100.0));
+        }
+    }
+
+    #[test]
+    fn test_hachure_concave() {
+        // a U shape: scanlines through the arms produce two segments each.
+        let u = vec![Point::new(0.0, 0.0), Point::new(30.0, 0.0), Point::new(30.0, 70.0), Point::new(70.0, 70.0),
+                     Point::new(70.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)];
+        let lines = hachure_lines(&u, 10.0, 0.0);
+        assert_eq!(lines.len(), 7 * 2 + 3);
+    }
+
+    #[test]
+    fn test_hachure_not_finite() {
+        let square = Rect::new(0.0, 0.0, 100.0, 100.0).corners();
+        let mut points = square.to_vec();
+        points.insert(2, Point::new(f64::NAN, 50.0));
+        assert_eq!(hachure_lines(&points, 10.0, 0.0).len(), 10);
+        assert!(hachure_lines(&square, 10.0, f64::NAN).is_empty());
+        assert!(hachure_lines(&square, f64::NAN, 0.0).is_empty());
+        assert!(hachure_lines(&square, f64::INFINITY, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_seeded() {
+        let (mut a, mut b) = (sketch(1.0, 1.0, 7), sketch(1.0, 1.0, 7));
+        assert_eq!(a.rough_line(10.0, 20.0, 200.0, 80.0, false), b.rough_line(10.0, 20.0, 200.0, 80.0, false));
+        assert_eq!(a.ellipse_points(50.0, 50.0, 40.0, 20.0, 1.0), b.ellipse_points(50.0, 50.0, 40.0, 20.0, 1.0));
+        let polygon = polygon_points(100.0, 100.0, 50.0, 6, 0.0);
+        assert_eq!(a.path_curves(&polygon, true), b.path_curves(&polygon, true));
+        // another seed draws it differently.
+        let mut c = sketch(1.0, 1.0, 8);
+        assert!(a.path_curves(&polygon, true) != c.path_curves(&polygon, true));
+    }
+
+    #[test]
+    fn test_roughness() {
+        let (start, end) = (Point::new(0.0, 0.0), Point::new(300.0, 0.0));
+        let wobble = |roughness: f64| {
+            let curve = sketch(roughness, 0.0, 3).rough_line(start.x, start.y, end.x, end.y, false);
+            [curve.p0, curve.p1, curve.p2, curve.p3].iter().fold(0.0, |max: f64, p| max.max(off_line(&start, &end, p)))
+        };
+        // with no roughness or bowing the stroke runs straight along the line.
+        assert_eq!(wobble(0.0), 0.0);
+        assert!(wobble(1.0) > 0.0);
+        assert!(approx_eq(wobble(3.0), wobble(1.0) * 3.0));
+
+        let center = Point::new(50.0, 50.0);
+        let smooth = sketch(0.0, 1.0, 3).ellipse_points(center.x, center.y, 40.0, 40.0, 1.0);
+        assert!(smooth.iter().all(|p| approx_eq(p.dist(&center), 40.0)));
+        let rough = sketch(1.0, 1.0, 3).ellipse_points(center.x, center.y, 40.0, 40.0, 1.0);
+        assert!(rough.iter().any(|p| (p.dist(&center) - 40.0).abs() > 0.1));
+    }
+
+    #[test]
+    fn test_bowing() {
+        let (start, end) = (Point::new(0.0, 0.0), Point::new(300.0, 0.0));
+        // without roughness the control points sit off the line by exactly the bow.
+        for &bowing in [0.0, 1.0, 4.0].iter() {
+            let mut s = sketch(0.0, bowing, 3);
+            let curve = s.rough_line(start.x, start.y, end.x, end.y, false);
+            let bow = bowing * s.max_offset * 300.0 / 200.0;
+            assert!(approx_eq(off_line(&start, &end, &curve.p1), bow));
+            assert!(approx_eq(off_line(&start, &end, &curve.p2), bow));
+            assert_eq!((curve.p0, curve.p3), (start, end));
+        }
+    }
+}